@@ -0,0 +1,82 @@
+//! Generates the compact ADF p-value table from `data/adf_p_value_lookup.csv`.
+//!
+//! The CSV holds the original 50,000-row lookup table. Rather than compiling every row
+//! into the wasm binary, we keep only the knots needed so that linear interpolation
+//! through them stays within `P_VALUE_TOLERANCE` of linear interpolation through the
+//! full table. Both interpolants are piecewise linear and our knots are a subset of the
+//! original ones, so checking the error at every original row bounds it everywhere.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const LOOKUP_CSV: &str = "data/adf_p_value_lookup.csv";
+const P_VALUE_TOLERANCE: f64 = 1e-4;
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", LOOKUP_CSV);
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let csv = fs::read_to_string(LOOKUP_CSV).expect("failed to read the ADF p-value lookup table");
+    let table: Vec<[f64; 2]> = csv
+        .lines()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut fields = line.trim().split(',');
+            let mut next = || -> f64 {
+                fields
+                    .next()
+                    .and_then(|field| field.trim().parse().ok())
+                    .unwrap_or_else(|| panic!("malformed row in {}: {}", LOOKUP_CSV, line))
+            };
+            [next(), next()]
+        })
+        .collect();
+    assert!(table.len() >= 2, "{} needs at least two rows", LOOKUP_CSV);
+    assert!(
+        table.windows(2).all(|w| w[0][0] < w[1][0]),
+        "{} must be sorted by test statistic",
+        LOOKUP_CSV
+    );
+
+    let knots = select_knots(&table, P_VALUE_TOLERANCE);
+
+    let mut out = String::new();
+    out.push_str(&format!(
+        "/// Maximum absolute difference from interpolating the full {}-row table.\n",
+        table.len()
+    ));
+    out.push_str(&format!("pub const P_VALUE_TOLERANCE: f64 = {:?};\n\n", P_VALUE_TOLERANCE));
+    out.push_str("const ADF_P_VALUE_KNOTS: &[[f64; 2]] = &[\n");
+    for &i in &knots {
+        out.push_str(&format!("    [{:?}, {:?}],\n", table[i][0], table[i][1]));
+    }
+    out.push_str("];\n");
+
+    let dest = Path::new(&env::var("OUT_DIR").unwrap()).join("adf_p_value_knots.rs");
+    fs::write(dest, out).expect("failed to write the generated p-value table");
+}
+
+/// Greedily extends each segment as far as the interpolation error allows.
+fn select_knots(table: &[[f64; 2]], tolerance: f64) -> Vec<usize> {
+    let mut knots = vec![0];
+    let mut start = 0;
+    while start < table.len() - 1 {
+        let mut end = start + 1;
+        while end + 1 < table.len() && segment_fits(table, start, end + 1, tolerance) {
+            end += 1;
+        }
+        knots.push(end);
+        start = end;
+    }
+    knots
+}
+
+fn segment_fits(table: &[[f64; 2]], start: usize, end: usize, tolerance: f64) -> bool {
+    let [x1, y1] = table[start];
+    let [x2, y2] = table[end];
+    table[start + 1..end]
+        .iter()
+        .all(|&[x, y]| (y1 + (x - x1) * (y2 - y1) / (x2 - x1) - y).abs() <= tolerance)
+}
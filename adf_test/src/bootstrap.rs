@@ -0,0 +1,182 @@
+//! Bootstrap null distribution for the ADF statistic. The unit root is imposed by
+//! resampling the first differences and re-integrating them, either through an AR sieve
+//! fitted to the differences (Park, 2003) or with the stationary block bootstrap (Politis
+//! and Romano, 1994). Each pseudo-series is tested with the same regression and lag as the
+//! data, so the p-value reflects the actual window length instead of the asymptotic tables.
+
+use std::str::FromStr;
+
+use nalgebra::{DMatrix, DVector};
+
+use crate::error::Error;
+use crate::ols;
+use crate::regression::Regression;
+use crate::rng::Rng;
+use crate::CriticalValues;
+
+/// How the restricted-model residuals are resampled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BootstrapMethod {
+    /// "sieve": i.i.d. draws of the residuals of an AR(lags) fitted to the differences
+    Sieve,
+    /// "block": stationary block bootstrap of the differences, mean block length n^(1/3)
+    StationaryBlock,
+}
+
+impl FromStr for BootstrapMethod {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sieve" => Ok(BootstrapMethod::Sieve),
+            "block" | "stationary-block" => Ok(BootstrapMethod::StationaryBlock),
+            other => Err(Error::InvalidOption(format!(
+                "unknown bootstrap method \"{}\" (expected \"sieve\" or \"block\")",
+                other
+            ))),
+        }
+    }
+}
+
+/// Bootstrap settings; the same seed always gives the same p-value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bootstrap {
+    pub method: BootstrapMethod,
+    pub replications: usize,
+    pub seed: u64,
+}
+
+impl Bootstrap {
+    pub fn new(method: BootstrapMethod, replications: usize, seed: u64) -> Self {
+        Bootstrap {
+            method,
+            replications,
+            seed,
+        }
+    }
+}
+
+/// Empirical p-value and 1%, 5% and 10% critical values of `test_statistic`, the ADF
+/// statistic of `data` with `lags` lagged differences.
+pub(crate) fn bootstrap_p_value(
+    data: &[f64],
+    regression: Regression,
+    lags: usize,
+    test_statistic: f64,
+    bootstrap: &Bootstrap,
+) -> Result<(f64, CriticalValues), Error> {
+    if bootstrap.replications < 20 {
+        return Err(Error::InvalidOption(format!(
+            "at least 20 bootstrap replications are needed, got {}",
+            bootstrap.replications
+        )));
+    }
+
+    let diff: Vec<f64> = data.windows(2).map(|w| w[1] - w[0]).collect();
+    let mut rng = Rng::new(bootstrap.seed);
+    let resampler = match bootstrap.method {
+        BootstrapMethod::Sieve => Resampler::sieve(&diff, lags)?,
+        BootstrapMethod::StationaryBlock => Resampler::stationary_block(&diff),
+    };
+
+    let mut statistics = Vec::with_capacity(bootstrap.replications);
+    let mut pseudo = vec![0.0; data.len()];
+    for _ in 0..bootstrap.replications {
+        pseudo[0] = data[0];
+        for (i, d) in resampler.draw(&mut rng).into_iter().enumerate() {
+            pseudo[i + 1] = pseudo[i] + d;
+        }
+        if let Ok(fit) = crate::calculate_adf_for_lags(&pseudo, lags as u32, regression, lags) {
+            statistics.push(fit.test_statistic);
+        }
+    }
+    // Degenerate draws (e.g. a constant pseudo-series) are dropped, but not too many
+    if statistics.len() * 2 < bootstrap.replications {
+        return Err(Error::SingularDesign);
+    }
+
+    statistics.sort_by(f64::total_cmp);
+    let below = statistics.iter().take_while(|&&s| s <= test_statistic).count();
+    let p_value = (below + 1) as f64 / (statistics.len() + 1) as f64;
+    let quantile = |level: f64| statistics[((level * statistics.len() as f64).ceil() as usize).saturating_sub(1)];
+
+    Ok((
+        p_value,
+        CriticalValues {
+            one_percent: quantile(0.01),
+            five_percent: quantile(0.05),
+            ten_percent: quantile(0.10),
+        },
+    ))
+}
+
+/// Generator of pseudo first differences under the unit root null.
+enum Resampler {
+    /// AR coefficients, centred residuals and the observed first `lags` differences
+    Sieve {
+        phi: Vec<f64>,
+        residuals: Vec<f64>,
+        start: Vec<f64>,
+        len: usize,
+    },
+    /// Demeaned differences and the probability of starting a new block
+    StationaryBlock { centred: Vec<f64>, restart: f64 },
+}
+
+impl Resampler {
+    /// Fits `diff_t = c + sum(phi_j * diff_{t-j}) + e_t`; the pseudo-differences start from
+    /// the observed first `lags` and are driven by i.i.d. draws of the centred residuals.
+    fn sieve(diff: &[f64], lags: usize) -> Result<Self, Error> {
+        let rows = diff.len() - lags;
+        let x = DMatrix::from_fn(rows, lags + 1, |i, k| if k == 0 { 1.0 } else { diff[lags + i - k] });
+        let y = DVector::from_column_slice(&diff[lags..]);
+        let fit = ols::ols(&x, &y)?;
+        let mean = fit.residuals.mean();
+
+        Ok(Resampler::Sieve {
+            phi: fit.params.iter().skip(1).copied().collect(),
+            residuals: fit.residuals.iter().map(|e| e - mean).collect(),
+            start: diff[..lags].to_vec(),
+            len: diff.len(),
+        })
+    }
+
+    /// Blocks of the demeaned differences with geometric lengths, wrapping around the end.
+    fn stationary_block(diff: &[f64]) -> Self {
+        let n = diff.len();
+        let mean = diff.iter().sum::<f64>() / n as f64;
+        Resampler::StationaryBlock {
+            centred: diff.iter().map(|d| d - mean).collect(),
+            restart: 1.0 / (n as f64).cbrt().ceil(),
+        }
+    }
+
+    fn draw(&self, rng: &mut Rng) -> Vec<f64> {
+        match self {
+            Resampler::Sieve {
+                phi,
+                residuals,
+                start,
+                len,
+            } => {
+                let mut out = Vec::with_capacity(*len);
+                out.extend_from_slice(start);
+                for t in start.len()..*len {
+                    let ar: f64 = phi.iter().enumerate().map(|(j, p)| p * out[t - 1 - j]).sum();
+                    out.push(ar + residuals[rng.below(residuals.len())]);
+                }
+                out
+            }
+            Resampler::StationaryBlock { centred, restart } => {
+                let n = centred.len();
+                let mut out = Vec::with_capacity(n);
+                let mut position = rng.below(n);
+                while out.len() < n {
+                    out.push(centred[position]);
+                    position = if rng.uniform() < *restart { rng.below(n) } else { (position + 1) % n };
+                }
+                out
+            }
+        }
+    }
+}
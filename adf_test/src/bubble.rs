@@ -0,0 +1,375 @@
+//! Phillips, Shi and Yu (2015) right-tailed tests for explosive behaviour. SADF is the
+//! largest ADF statistic over windows anchored at the first observation, GSADF the largest
+//! over all windows of at least `min_window` bars, and the backward SADF sequence dates
+//! the explosive episodes. All regressions are the constant-only ADF regression with a
+//! fixed lag, solved from prefix sums of the row cross-products so that the O(T^2) windows
+//! stay affordable; critical values come from a seeded Monte Carlo under a random walk.
+//!
+//! The prefix sums are the reason these regressions skip the QR solver of [`crate::ols`]:
+//! each window costs `O(k^3)` from its cross-products instead of `O(window * k^2)`, which
+//! is what keeps GSADF's quadratic number of windows tractable. The series is standardised
+//! before the sums are taken, the pivots are tested relative to their diagonals, and any
+//! window whose normal equations are too ill-conditioned is refitted through QR.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{self, Error};
+use crate::regression::Regression;
+use crate::rng::Rng;
+use crate::CriticalValues;
+
+/// Smallest Cholesky pivot, relative to its diagonal, trusted before refitting with QR.
+const PIVOT_TOLERANCE: f64 = 1e-10;
+
+#[wasm_bindgen]
+pub struct BubbleTestResult {
+    pub sadf: f64,
+    pub gsadf: f64,
+    sadf_critical_values: CriticalValues,
+    gsadf_critical_values: CriticalValues,
+    bsadf: Vec<f64>,
+    bsadf_critical_values: Vec<f64>,
+    explosive: Vec<bool>,
+    episodes: Vec<u32>,
+    pub min_window: u32,
+    pub lags: u32,
+    /// GSADF above its 5% critical value
+    pub is_explosive: bool,
+}
+
+#[wasm_bindgen]
+impl BubbleTestResult {
+    /// Right-tailed: the statistic must exceed the value to reject at that level
+    #[wasm_bindgen(getter)]
+    pub fn sadf_critical_values(&self) -> JsValue {
+        self.sadf_critical_values.to_js()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn gsadf_critical_values(&self) -> JsValue {
+        self.gsadf_critical_values.to_js()
+    }
+
+    /// Backward SADF for each end observation; NaN before the first full window
+    #[wasm_bindgen(getter)]
+    pub fn bsadf(&self) -> Vec<f64> {
+        self.bsadf.clone()
+    }
+
+    /// 5% Monte Carlo critical value of the backward SADF for each end observation
+    #[wasm_bindgen(getter)]
+    pub fn bsadf_critical_values(&self) -> Vec<f64> {
+        self.bsadf_critical_values.clone()
+    }
+
+    /// Array of booleans: backward SADF above its 5% critical value at each observation
+    #[wasm_bindgen(getter)]
+    pub fn explosive(&self) -> JsValue {
+        self.explosive
+            .iter()
+            .map(|&flag| JsValue::from_bool(flag))
+            .collect::<js_sys::Array>()
+            .into()
+    }
+
+    /// Explosive episodes lasting at least `ln(T)` bars, as `[start, end, start, end, ...]`
+    /// with inclusive observation indices
+    #[wasm_bindgen(getter)]
+    pub fn episodes(&self) -> Vec<u32> {
+        self.episodes.clone()
+    }
+}
+
+impl BubbleTestResult {
+    pub fn sadf_critical_value_table(&self) -> CriticalValues {
+        self.sadf_critical_values
+    }
+
+    pub fn gsadf_critical_value_table(&self) -> CriticalValues {
+        self.gsadf_critical_values
+    }
+
+    pub fn explosive_flags(&self) -> &[bool] {
+        &self.explosive
+    }
+}
+
+/// SADF, GSADF and backward SADF date-stamping with `lags` lagged differences. Leave
+/// `min_window` `undefined` for the rule `T * (0.01 + 1.8 / sqrt(T))`. Each Monte Carlo
+/// replication repeats the full O(T^2) window search, so keep `replications` modest on
+/// long series; the same `seed` always gives the same critical values.
+#[wasm_bindgen]
+pub fn calculate_bubble_test(
+    data: Vec<f64>,
+    min_window: Option<u32>,
+    lags: u32,
+    replications: u32,
+    seed: u32,
+) -> Result<BubbleTestResult, JsError> {
+    Ok(bubble_test(
+        &data,
+        min_window.map(|w| w as usize),
+        lags as usize,
+        replications as usize,
+        seed as u64,
+    )?)
+}
+
+pub fn bubble_test(
+    data: &[f64],
+    min_window: Option<usize>,
+    lags: usize,
+    replications: usize,
+    seed: u64,
+) -> Result<BubbleTestResult, Error> {
+    let n = data.len();
+    let smallest = (2 * lags + 5).max(crate::min_observations(Regression::Constant));
+    let min_window = min_window.unwrap_or_else(|| default_min_window(n)).max(smallest);
+    error::validate_series(data, min_window + 1)?;
+    if replications < 20 {
+        return Err(Error::InvalidOption(format!(
+            "at least 20 Monte Carlo replications are needed, got {}",
+            replications
+        )));
+    }
+
+    let observed = Recursion::new(data, lags, min_window);
+
+    // Null distribution: Gaussian random walks of the same length
+    let mut rng = Rng::new(seed);
+    let mut sadf_draws = Vec::with_capacity(replications);
+    let mut gsadf_draws = Vec::with_capacity(replications);
+    let mut bsadf_draws = vec![Vec::with_capacity(replications); n];
+    let mut walk = vec![0.0; n];
+    for _ in 0..replications {
+        for t in 1..n {
+            walk[t] = walk[t - 1] + rng.standard_normal();
+        }
+        let draw = Recursion::new(&walk, lags, min_window);
+        sadf_draws.push(draw.sadf);
+        gsadf_draws.push(draw.gsadf);
+        for (r2, value) in draw.bsadf.iter().enumerate() {
+            if value.is_finite() {
+                bsadf_draws[r2].push(*value);
+            }
+        }
+    }
+
+    let bsadf_critical_values: Vec<f64> = bsadf_draws
+        .iter_mut()
+        .map(|draws| if draws.is_empty() { f64::NAN } else { quantile(draws, 0.95) })
+        .collect();
+    let explosive: Vec<bool> = observed
+        .bsadf
+        .iter()
+        .zip(&bsadf_critical_values)
+        .map(|(stat, cv)| stat > cv)
+        .collect();
+    let gsadf_critical_values = right_tail_critical_values(&mut gsadf_draws);
+
+    Ok(BubbleTestResult {
+        sadf: observed.sadf,
+        gsadf: observed.gsadf,
+        sadf_critical_values: right_tail_critical_values(&mut sadf_draws),
+        gsadf_critical_values,
+        episodes: episodes(&explosive, ((n as f64).ln().floor() as usize).max(1)),
+        bsadf: observed.bsadf,
+        bsadf_critical_values,
+        explosive,
+        min_window: min_window as u32,
+        lags: lags as u32,
+        is_explosive: observed.gsadf > gsadf_critical_values.five_percent,
+    })
+}
+
+/// Phillips, Shi and Yu's minimum window `T * (0.01 + 1.8 / sqrt(T))`.
+pub fn default_min_window(nobs: usize) -> usize {
+    let n = nobs as f64;
+    (n * (0.01 + 1.8 / n.sqrt())).floor() as usize
+}
+
+/// SADF, GSADF and the backward SADF sequence of one series.
+struct Recursion {
+    sadf: f64,
+    gsadf: f64,
+    bsadf: Vec<f64>,
+}
+
+impl Recursion {
+    fn new(data: &[f64], lags: usize, min_window: usize) -> Self {
+        let n = data.len();
+        let sums = PrefixCrossProducts::new(data, lags);
+        let mut scratch = Vec::new();
+        let mut bsadf = vec![f64::NAN; n];
+        let mut sadf = f64::NEG_INFINITY;
+
+        for (r2, value) in bsadf.iter_mut().enumerate().skip(min_window - 1) {
+            let mut sup = f64::NEG_INFINITY;
+            for r1 in 0..=r2 + 1 - min_window {
+                let stat = sums.adf(data, r1, r2, &mut scratch);
+                if r1 == 0 {
+                    sadf = sadf.max(stat);
+                }
+                sup = sup.max(stat);
+            }
+            if sup.is_finite() {
+                *value = sup;
+            }
+        }
+        let gsadf = bsadf.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        Recursion { sadf, gsadf, bsadf }
+    }
+}
+
+/// Running sums of `row * row'` for the rows `[1, y_{t-1}, lagged differences, dy_t]` of
+/// the standardised series, so that any window's cross-products are one subtraction.
+struct PrefixCrossProducts {
+    lags: usize,
+    width: usize,
+    sums: Vec<f64>,
+}
+
+impl PrefixCrossProducts {
+    fn new(data: &[f64], lags: usize) -> Self {
+        let n = data.len() as f64;
+        let mean = data.iter().sum::<f64>() / n;
+        let scale = (data.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+        let scale = if scale > 0.0 { scale } else { 1.0 };
+        let levels: Vec<f64> = data.iter().map(|v| (v - mean) / scale).collect();
+        let diffs: Vec<f64> = levels.windows(2).map(|w| w[1] - w[0]).collect();
+
+        let width = lags + 3;
+        let size = width * width;
+        let mut sums = vec![0.0; (diffs.len() + 1) * size];
+        let mut row = vec![0.0; width];
+        for d in 0..diffs.len() {
+            let (done, rest) = sums.split_at_mut((d + 1) * size);
+            let previous = &done[d * size..];
+            let current = &mut rest[..size];
+            current.copy_from_slice(previous);
+            if d < lags {
+                continue;
+            }
+            row[0] = 1.0;
+            row[1] = levels[d];
+            for j in 1..=lags {
+                row[1 + j] = diffs[d - j];
+            }
+            row[width - 1] = diffs[d];
+            for (i, a) in row.iter().enumerate() {
+                for (j, b) in row.iter().enumerate() {
+                    current[i * width + j] += a * b;
+                }
+            }
+        }
+        PrefixCrossProducts { lags, width, sums }
+    }
+
+    /// ADF statistic of `data[r1..=r2]`, or NaN when it cannot be computed. `scratch`
+    /// is reused between calls to avoid allocating in the O(T^2) loop.
+    fn adf(&self, data: &[f64], r1: usize, r2: usize, scratch: &mut Vec<f64>) -> f64 {
+        let (first, last) = (r1 + self.lags, r2);
+        let size = self.width * self.width;
+        scratch.clear();
+        scratch.extend(
+            self.sums[last * size..(last + 1) * size]
+                .iter()
+                .zip(&self.sums[first * size..(first + 1) * size])
+                .map(|(a, b)| a - b),
+        );
+        scratch.resize(size + self.width - 1, 0.0);
+        match gamma_t_statistic(scratch, self.width, last - first) {
+            Some(stat) => stat,
+            // Badly conditioned cross-products: refit the window with the QR solver
+            None => crate::calculate_adf_for_lags(&data[r1..=r2], self.lags as u32, Regression::Constant, self.lags)
+                .map_or(f64::NAN, |fit| fit.test_statistic),
+        }
+    }
+}
+
+/// t-statistic of the coefficient on y_{t-1} (regressor 1) from the cross-products
+/// `[X'X X'y; y'X y'y]` stored row-major at the start of `scratch`, followed by room for
+/// one more column. The contents are overwritten.
+///
+/// With `X'X = L L'` and `z = L^-1 X'y`, the residual sum of squares is `y'y - z'z`, and with
+/// `w = L^-1 e_1` the coefficient is `w'z` and its variance `sigma^2 w'w`.
+fn gamma_t_statistic(scratch: &mut [f64], width: usize, nobs: usize) -> Option<f64> {
+    let k = width - 1;
+    let (m, w) = scratch.split_at_mut(width * width);
+    if nobs <= k {
+        return None;
+    }
+    // In-place Cholesky of the leading k x k block into its lower triangle
+    for j in 0..k {
+        let diagonal = m[j * width + j];
+        let pivot = diagonal - (0..j).map(|p| m[j * width + p].powi(2)).sum::<f64>();
+        if pivot.is_nan() || pivot <= PIVOT_TOLERANCE * diagonal {
+            return None;
+        }
+        let pivot = pivot.sqrt();
+        m[j * width + j] = pivot;
+        for i in j + 1..k {
+            let dot: f64 = (0..j).map(|p| m[i * width + p] * m[j * width + p]).sum();
+            m[i * width + j] = (m[i * width + j] - dot) / pivot;
+        }
+    }
+
+    // Forward substitution for z (in the last column) and w
+    let mut w_dot_z = 0.0;
+    let mut w_norm = 0.0;
+    let mut z_norm = 0.0;
+    for i in 0..k {
+        let l_ii = m[i * width + i];
+        let z = (m[i * width + k] - (0..i).map(|p| m[i * width + p] * m[p * width + k]).sum::<f64>()) / l_ii;
+        m[i * width + k] = z;
+        let e = if i == 1 { 1.0 } else { 0.0 };
+        w[i] = (e - (0..i).map(|p| m[i * width + p] * w[p]).sum::<f64>()) / l_ii;
+        w_dot_z += w[i] * z;
+        w_norm += w[i] * w[i];
+        z_norm += z * z;
+    }
+
+    let yty = m[k * width + k];
+    let ssr = yty - z_norm;
+    if ssr.is_nan() || ssr <= PIVOT_TOLERANCE * yty {
+        return None;
+    }
+    let sigma2 = ssr / (nobs - k) as f64;
+    let t = w_dot_z / (sigma2 * w_norm).sqrt();
+    t.is_finite().then_some(t)
+}
+
+/// 99%, 95% and 90% quantiles of the simulated statistics, reported as the 1%, 5% and 10%
+/// critical values of the right-tailed test.
+fn right_tail_critical_values(draws: &mut [f64]) -> CriticalValues {
+    CriticalValues {
+        one_percent: quantile(draws, 0.99),
+        five_percent: quantile(draws, 0.95),
+        ten_percent: quantile(draws, 0.90),
+    }
+}
+
+fn quantile(draws: &mut [f64], level: f64) -> f64 {
+    draws.sort_by(f64::total_cmp);
+    let index = ((level * draws.len() as f64).ceil() as usize).clamp(1, draws.len()) - 1;
+    draws[index]
+}
+
+/// Runs of `true` lasting at least `min_duration` observations, as inclusive index pairs.
+fn episodes(explosive: &[bool], min_duration: usize) -> Vec<u32> {
+    let mut result = Vec::new();
+    let mut start = None;
+    for (t, &flag) in explosive.iter().chain(std::iter::once(&false)).enumerate() {
+        match (flag, start) {
+            (true, None) => start = Some(t),
+            (false, Some(s)) => {
+                if t - s >= min_duration {
+                    result.extend([s as u32, (t - 1) as u32]);
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    result
+}
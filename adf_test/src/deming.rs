@@ -0,0 +1,125 @@
+//! Deming regression hedge ratio, which allows for noise in both legs. With an error-variance
+//! ratio of one it is orthogonal regression (total least squares).
+//!
+//! OLS of A on B and OLS of B on A give hedge ratios that are not reciprocals of each other,
+//! so the spread depends on which leg is called A. Deming regression with ratio `delta =
+//! Var(noise in A) / Var(noise in B)` is symmetric: swapping the legs and passing `1 / delta`
+//! gives `1 / beta`. Orthogonal regression measures distance in price units, so with legs of
+//! very different price levels a ratio matching their scales is usually the better choice.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::hedge::{self, CentredMoments, HedgeSeries, WindowMoments};
+
+#[wasm_bindgen]
+pub struct DemingResult {
+    pub alpha: f64,
+    /// Hedge ratio: units of B per unit of A
+    pub beta: f64,
+    /// Error-variance ratio the fit used
+    pub error_variance_ratio: f64,
+    pub nobs: usize,
+    series: HedgeSeries,
+}
+
+#[wasm_bindgen]
+impl DemingResult {
+    /// The fitted alpha and beta on every bar, with the spread they imply
+    #[wasm_bindgen(getter)]
+    pub fn series(&self) -> HedgeSeries {
+        self.series.clone()
+    }
+}
+
+impl DemingResult {
+    pub fn hedge_series(&self) -> &HedgeSeries {
+        &self.series
+    }
+}
+
+/// Deming regression of `prices_a` on `prices_b`; `error_variance_ratio` defaults to 1,
+/// orthogonal regression.
+#[wasm_bindgen]
+pub fn calculate_deming_hedge(prices_a: Vec<f64>, prices_b: Vec<f64>, error_variance_ratio: Option<f64>) -> Result<DemingResult, JsError> {
+    Ok(deming_hedge(&prices_a, &prices_b, error_variance_ratio)?)
+}
+
+/// Deming regression over a window of `window` bars ending at each bar, with the same
+/// warm-up and fallback as the rolling OLS.
+#[wasm_bindgen]
+pub fn calculate_rolling_deming(
+    prices_a: Vec<f64>,
+    prices_b: Vec<f64>,
+    window: usize,
+    error_variance_ratio: Option<f64>,
+) -> Result<HedgeSeries, JsError> {
+    Ok(rolling_deming(&prices_a, &prices_b, window, error_variance_ratio)?)
+}
+
+pub fn deming_hedge(prices_a: &[f64], prices_b: &[f64], error_variance_ratio: Option<f64>) -> Result<DemingResult, Error> {
+    let delta = validate_ratio(error_variance_ratio)?;
+    hedge::validate_pair(prices_a, prices_b, 3)?;
+
+    let moments = WindowMoments::from_window(prices_a, prices_b).centred();
+    let (alpha, beta) = fit(&moments, delta).ok_or(Error::SingularDesign)?;
+
+    let n = prices_a.len();
+    Ok(DemingResult {
+        alpha,
+        beta,
+        error_variance_ratio: delta,
+        nobs: n,
+        series: HedgeSeries::new(prices_a, prices_b, vec![alpha; n], vec![beta; n]),
+    })
+}
+
+pub fn rolling_deming(
+    prices_a: &[f64],
+    prices_b: &[f64],
+    window: usize,
+    error_variance_ratio: Option<f64>,
+) -> Result<HedgeSeries, Error> {
+    let delta = validate_ratio(error_variance_ratio)?;
+    if window < 2 {
+        return Err(Error::InvalidOption(format!("window must be at least 2 bars, got {}", window)));
+    }
+    hedge::validate_pair(prices_a, prices_b, 1)?;
+
+    let mut alphas = Vec::with_capacity(prices_a.len());
+    let mut betas = Vec::with_capacity(prices_a.len());
+    hedge::for_each_window(prices_a, prices_b, window, |m| {
+        let (alpha, beta) = fit(&m, delta).unwrap_or((0.0, 1.0));
+        alphas.push(alpha);
+        betas.push(beta);
+    });
+    Ok(HedgeSeries::new(prices_a, prices_b, alphas, betas))
+}
+
+fn validate_ratio(error_variance_ratio: Option<f64>) -> Result<f64, Error> {
+    let delta = error_variance_ratio.unwrap_or(1.0);
+    if !(delta > 0.0 && delta.is_finite()) {
+        return Err(Error::InvalidOption(format!(
+            "error_variance_ratio must be positive, got {}",
+            delta
+        )));
+    }
+    Ok(delta)
+}
+
+/// `(alpha, beta)` from the centred moments, or `None` when the legs are uncorrelated over
+/// the window and the line is either flat or vertical.
+fn fit(m: &CentredMoments, delta: f64) -> Option<(f64, f64)> {
+    if m.s_ab == 0.0 {
+        return None;
+    }
+    let d = m.s_aa - delta * m.s_bb;
+    // Written so that neither sign of d loses digits to cancellation
+    let root = (d * d + 4.0 * delta * m.s_ab * m.s_ab).sqrt();
+    let beta = if d >= 0.0 {
+        (d + root) / (2.0 * m.s_ab)
+    } else {
+        2.0 * delta * m.s_ab / (root - d)
+    };
+    Some((m.mean_a - beta * m.mean_b, beta))
+}
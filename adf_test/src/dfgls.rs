@@ -0,0 +1,112 @@
+//! Elliott, Rothenberg and Stock (1996) DF-GLS test. The series is detrended by GLS on
+//! quasi-differenced data before running a Dickey-Fuller regression without deterministic
+//! terms, which gives noticeably more power than ADF on short windows.
+//!
+//! With a constant only, the statistic follows the no-constant Dickey-Fuller distribution
+//! and gets MacKinnon p-values. With a trend it has its own distribution, for which only
+//! the ERS critical values are tabulated here: the p-value is NaN and stationarity is
+//! decided at the 5% critical value.
+
+use nalgebra::{DMatrix, DVector};
+use wasm_bindgen::prelude::*;
+
+use crate::error::{self, Error};
+use crate::mackinnon;
+use crate::ols;
+use crate::regression::Regression;
+use crate::{AdfOptions, CompleteAdfResult, CriticalValues};
+
+/// Local-to-unity parameter c-bar of the quasi-differencing, `alpha = 1 + c-bar / T`.
+const C_BAR_CONSTANT: f64 = -7.0;
+const C_BAR_TREND: f64 = -13.5;
+
+/// ERS (1996) Table 1 critical values for the trend case at T = 50, 100, 200 and infinity.
+const ERS_TREND_SAMPLE_SIZES: [f64; 4] = [50.0, 100.0, 200.0, f64::INFINITY];
+const ERS_TREND_CRITICAL_VALUES: [[f64; 3]; 4] = [
+    [-3.77, -3.19, -2.89],
+    [-3.58, -3.03, -2.74],
+    [-3.46, -2.93, -2.64],
+    [-3.48, -2.89, -2.57],
+];
+
+/// DF-GLS test with the deterministic terms ("c" or "ct"), lag criterion and maximum lag
+/// from `options`. The result has the same shape as the ADF one; its report describes the
+/// regression on the GLS-detrended series, and the p-value is NaN for "ct".
+#[wasm_bindgen]
+pub fn calculate_dfgls_test(data: Vec<f64>, options: &AdfOptions) -> Result<CompleteAdfResult, JsError> {
+    Ok(dfgls_test(&data, options)?)
+}
+
+pub fn dfgls_test(data: &[f64], options: &AdfOptions) -> Result<CompleteAdfResult, Error> {
+    let regression = options.regression();
+    if !matches!(regression, Regression::Constant | Regression::ConstantTrend) {
+        return Err(Error::InvalidOption(format!(
+            "DF-GLS supports the \"c\" and \"ct\" regressions, not \"{}\"",
+            regression
+        )));
+    }
+    error::validate_series(data, crate::min_observations(regression))?;
+
+    let detrended = gls_detrend(data, regression)?;
+    let search = crate::search_lags(&detrended, Regression::NoConstant, options.autolag(), options.max_lags())?;
+
+    let nobs = search.nobs();
+    let (p_value, critical_values) = match regression {
+        // With a constant only, the statistic has the Dickey-Fuller no-constant distribution
+        Regression::Constant => (
+            mackinnon::mackinnonp(search.test_statistic(), Regression::NoConstant),
+            mackinnon::critical_values(Regression::NoConstant, nobs),
+        ),
+        // Only the ERS critical values are published for the trend case
+        _ => (f64::NAN, ers_trend_critical_values(nobs)),
+    };
+
+    Ok(CompleteAdfResult::from_search(search, Regression::NoConstant, p_value, critical_values))
+}
+
+/// Removes the deterministic terms estimated by OLS on quasi-differenced data.
+pub fn gls_detrend(data: &[f64], regression: Regression) -> Result<Vec<f64>, Error> {
+    let nobs = data.len();
+    let c_bar = match regression {
+        Regression::ConstantTrend => C_BAR_TREND,
+        _ => C_BAR_CONSTANT,
+    };
+    let alpha = 1.0 + c_bar / nobs as f64;
+
+    let z = regression.design(nobs);
+    let quasi_difference = |i: usize, current: f64, previous: f64| {
+        if i == 0 {
+            current
+        } else {
+            current - alpha * previous
+        }
+    };
+    let y_q = DVector::from_fn(nobs, |i, _| quasi_difference(i, data[i], if i > 0 { data[i - 1] } else { 0.0 }));
+    let z_q = DMatrix::from_fn(nobs, z.ncols(), |i, k| {
+        quasi_difference(i, z[(i, k)], if i > 0 { z[(i - 1, k)] } else { 0.0 })
+    });
+
+    let delta = ols::ols(&z_q, &y_q)?.params;
+    let trend = z * delta;
+    Ok(data.iter().zip(trend.iter()).map(|(y, t)| y - t).collect())
+}
+
+/// ERS trend-case critical values interpolated linearly in 1/T.
+fn ers_trend_critical_values(nobs: usize) -> CriticalValues {
+    let target = 1.0 / nobs.max(1) as f64;
+    let inv: Vec<f64> = ERS_TREND_SAMPLE_SIZES.iter().map(|t| 1.0 / t).collect();
+    let row = if target >= inv[0] {
+        ERS_TREND_CRITICAL_VALUES[0]
+    } else {
+        let k = (0..inv.len() - 1)
+            .find(|&k| target <= inv[k] && target >= inv[k + 1])
+            .unwrap_or(inv.len() - 2);
+        let w = (target - inv[k + 1]) / (inv[k] - inv[k + 1]);
+        [0, 1, 2].map(|j| w * ERS_TREND_CRITICAL_VALUES[k][j] + (1.0 - w) * ERS_TREND_CRITICAL_VALUES[k + 1][j])
+    };
+    CriticalValues {
+        one_percent: row[0],
+        five_percent: row[1],
+        ten_percent: row[2],
+    }
+}
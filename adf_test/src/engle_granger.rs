@@ -0,0 +1,109 @@
+//! Engle and Granger (1987) two-step cointegration test. The hedge regression of `y` on
+//! `x` is estimated by OLS and its residuals are tested with an ADF regression without
+//! deterministic terms. Because the residuals are fitted, the statistic is compared with
+//! MacKinnon's two-variable tables rather than the plain Dickey-Fuller ones, following
+//! statsmodels' `coint`.
+
+use nalgebra::{DMatrix, DVector};
+use wasm_bindgen::prelude::*;
+
+use crate::error::{self, Error};
+use crate::mackinnon;
+use crate::ols;
+use crate::regression::Regression;
+use crate::{AdfOptions, CriticalValues};
+
+/// Number of series in the cointegrating regression, MacKinnon's N.
+const N_VARIABLES: usize = 2;
+
+#[wasm_bindgen]
+pub struct EngleGrangerResult {
+    /// ADF tau statistic of the hedge regression residuals
+    pub test_statistic: f64,
+    /// MacKinnon two-variable p-value
+    pub p_value: f64,
+    /// Lagged differences in the residual ADF regression
+    pub lags: u32,
+    /// Observations in the residual ADF regression
+    pub nobs: u32,
+    /// Intercept of the hedge regression (0 without a constant)
+    pub alpha: f64,
+    /// Hedge ratio: units of `x` per unit of `y`
+    pub beta: f64,
+    critical_values: CriticalValues,
+    residuals: Vec<f64>,
+    pub is_cointegrated: bool,
+}
+
+#[wasm_bindgen]
+impl EngleGrangerResult {
+    #[wasm_bindgen(getter)]
+    pub fn critical_values(&self) -> JsValue {
+        self.critical_values.to_js()
+    }
+
+    /// The spread `y - alpha - beta * x` (less any trend terms) that was tested
+    #[wasm_bindgen(getter)]
+    pub fn residuals(&self) -> Vec<f64> {
+        self.residuals.clone()
+    }
+}
+
+impl EngleGrangerResult {
+    pub fn critical_value_table(&self) -> CriticalValues {
+        self.critical_values
+    }
+}
+
+/// Engle-Granger test of `y` against `x`. The regression in `options` gives the
+/// deterministic terms of the hedge regression ("c", "ct" or "ctt"); the lag criterion and
+/// maximum lag apply to the residual ADF regression.
+#[wasm_bindgen]
+pub fn calculate_engle_granger_test(y: Vec<f64>, x: Vec<f64>, options: &AdfOptions) -> Result<EngleGrangerResult, JsError> {
+    Ok(engle_granger(&y, &x, options)?)
+}
+
+pub fn engle_granger(y: &[f64], x: &[f64], options: &AdfOptions) -> Result<EngleGrangerResult, Error> {
+    if y.len() != x.len() {
+        return Err(Error::InvalidOption(format!(
+            "y and x must have the same length ({} and {})",
+            y.len(),
+            x.len()
+        )));
+    }
+    let regression = options.regression();
+    // Fails early for the "n" specification, which MacKinnon does not tabulate for pairs
+    mackinnon::cointegration_critical_values(regression, N_VARIABLES, y.len())?;
+
+    let required = crate::min_observations(regression) + 1;
+    error::validate_series(y, required)?;
+    error::validate_series(x, required)?;
+
+    // Hedge regression: y on the deterministic terms and x
+    let n = y.len();
+    let n_trend = regression.n_terms();
+    let deterministic = regression.design(n);
+    let design = DMatrix::from_fn(n, n_trend + 1, |i, k| if k < n_trend { deterministic[(i, k)] } else { x[i] });
+    let fit = ols::ols(&design, &DVector::from_column_slice(y))?;
+    let residuals: Vec<f64> = fit.residuals.iter().copied().collect();
+
+    let search = crate::search_lags(&residuals, Regression::NoConstant, options.autolag(), options.max_lags())?;
+    let test_statistic = search.test_statistic();
+    let nobs = search.nobs();
+    let p_value = mackinnon::cointegration_p_value(test_statistic, regression, N_VARIABLES)?;
+    // As in statsmodels, the response surface is evaluated at one less than the series
+    // length rather than at the rows of the residual ADF regression
+    let critical_values = mackinnon::cointegration_critical_values(regression, N_VARIABLES, n - 1)?;
+
+    Ok(EngleGrangerResult {
+        test_statistic,
+        p_value,
+        lags: search.lags() as u32,
+        nobs: nobs as u32,
+        alpha: if n_trend > 0 { fit.params[0] } else { 0.0 },
+        beta: fit.params[n_trend],
+        critical_values,
+        residuals,
+        is_cointegrated: crate::determine_stationarity(test_statistic, &critical_values),
+    })
+}
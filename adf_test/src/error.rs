@@ -0,0 +1,55 @@
+use std::fmt;
+
+/// Why a statistic could not be computed. Converts into a `JsError` carrying the
+/// `Display` message, so the worker can report the reason instead of a default result.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// Fewer observations than the requested test needs
+    InsufficientData { required: usize, actual: usize },
+    /// A NaN or infinite value in the input
+    NonFiniteInput { index: usize },
+    /// Every observation has the same value
+    ConstantSeries,
+    /// The design matrix is rank deficient, so no regression could be estimated
+    SingularDesign,
+    /// An option string or value that is not supported
+    InvalidOption(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InsufficientData { required, actual } => write!(
+                f,
+                "insufficient data: {} observations provided, at least {} required",
+                actual, required
+            ),
+            Error::NonFiniteInput { index } => {
+                write!(f, "non-finite input: value at index {} is NaN or infinite", index)
+            }
+            Error::ConstantSeries => write!(f, "constant series: all observations are equal"),
+            Error::SingularDesign => write!(f, "singular design: the regression matrix is rank deficient"),
+            Error::InvalidOption(message) => write!(f, "invalid option: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Checks the shared preconditions of the unit root tests: enough points, all finite and
+/// not all equal.
+pub(crate) fn validate_series(data: &[f64], required: usize) -> Result<(), Error> {
+    if data.len() < required {
+        return Err(Error::InsufficientData {
+            required,
+            actual: data.len(),
+        });
+    }
+    if let Some(index) = data.iter().position(|value| !value.is_finite()) {
+        return Err(Error::NonFiniteInput { index });
+    }
+    if data.iter().all(|&value| value == data[0]) {
+        return Err(Error::ConstantSeries);
+    }
+    Ok(())
+}
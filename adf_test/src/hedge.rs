@@ -0,0 +1,218 @@
+//! Output shared by the hedge-ratio models: per-bar intercept, hedge ratio and the spread
+//! `a - (alpha + beta * b)` they imply, plus the rolling z-score the pages trade on.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{self, Error};
+
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default)]
+pub struct HedgeSeries {
+    alphas: Vec<f64>,
+    betas: Vec<f64>,
+    spreads: Vec<f64>,
+}
+
+#[wasm_bindgen]
+impl HedgeSeries {
+    #[wasm_bindgen(getter)]
+    pub fn alphas(&self) -> Vec<f64> {
+        self.alphas.clone()
+    }
+
+    /// Hedge ratio: units of B per unit of A
+    #[wasm_bindgen(getter)]
+    pub fn betas(&self) -> Vec<f64> {
+        self.betas.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn spreads(&self) -> Vec<f64> {
+        self.spreads.clone()
+    }
+
+    /// Z-score of each spread against the previous `lookback` spreads including itself, with
+    /// the sample standard deviation; 0 until the window is full, as in the worker.
+    pub fn z_scores(&self, lookback: usize) -> Vec<f64> {
+        rolling_z_scores(&self.spreads, lookback)
+    }
+}
+
+impl HedgeSeries {
+    /// Series for the coefficients `alphas[i]`, `betas[i]` applied to the prices at bar `i`.
+    pub(crate) fn new(prices_a: &[f64], prices_b: &[f64], alphas: Vec<f64>, betas: Vec<f64>) -> Self {
+        let spreads = prices_a
+            .iter()
+            .zip(prices_b)
+            .zip(alphas.iter().zip(&betas))
+            .map(|((a, b), (alpha, beta))| a - (alpha + beta * b))
+            .collect();
+        HedgeSeries { alphas, betas, spreads }
+    }
+
+    /// Appends a bar's coefficients and the spread they give at its prices.
+    pub(crate) fn push(&mut self, price_a: f64, price_b: f64, alpha: f64, beta: f64) {
+        self.alphas.push(alpha);
+        self.betas.push(beta);
+        self.spreads.push(price_a - (alpha + beta * price_b));
+    }
+
+    pub fn len(&self) -> usize {
+        self.spreads.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spreads.is_empty()
+    }
+}
+
+/// Sums of a pair over a window that bars can enter and leave in O(1), for the rolling
+/// hedge models. Prices are taken relative to an anchor bar so that the centred moments do
+/// not lose their digits to levels in the thousands.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct WindowMoments {
+    anchor_a: f64,
+    anchor_b: f64,
+    count: usize,
+    sum_a: f64,
+    sum_b: f64,
+    sum_aa: f64,
+    sum_bb: f64,
+    sum_ab: f64,
+}
+
+/// Centred moments of a window: means and the sums of squared and cross deviations.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct CentredMoments {
+    pub mean_a: f64,
+    pub mean_b: f64,
+    pub s_aa: f64,
+    pub s_bb: f64,
+    pub s_ab: f64,
+}
+
+impl WindowMoments {
+    pub fn new(anchor_a: f64, anchor_b: f64) -> Self {
+        WindowMoments {
+            anchor_a,
+            anchor_b,
+            count: 0,
+            sum_a: 0.0,
+            sum_b: 0.0,
+            sum_aa: 0.0,
+            sum_bb: 0.0,
+            sum_ab: 0.0,
+        }
+    }
+
+    /// Moments of a whole window, computed afresh and anchored on its first bar.
+    pub fn from_window(prices_a: &[f64], prices_b: &[f64]) -> Self {
+        let mut moments = WindowMoments::new(prices_a[0], prices_b[0]);
+        for (&a, &b) in prices_a.iter().zip(prices_b) {
+            moments.push(a, b);
+        }
+        moments
+    }
+
+    pub fn push(&mut self, a: f64, b: f64) {
+        let (a, b) = (a - self.anchor_a, b - self.anchor_b);
+        self.count += 1;
+        self.sum_a += a;
+        self.sum_b += b;
+        self.sum_aa += a * a;
+        self.sum_bb += b * b;
+        self.sum_ab += a * b;
+    }
+
+    pub fn pop(&mut self, a: f64, b: f64) {
+        let (a, b) = (a - self.anchor_a, b - self.anchor_b);
+        self.count -= 1;
+        self.sum_a -= a;
+        self.sum_b -= b;
+        self.sum_aa -= a * a;
+        self.sum_bb -= b * b;
+        self.sum_ab -= a * b;
+    }
+
+    /// Centred moments; the window must not be empty.
+    pub fn centred(&self) -> CentredMoments {
+        let n = self.count as f64;
+        let (mean_a, mean_b) = (self.sum_a / n, self.sum_b / n);
+        // Deviations that are pure rounding noise are clamped to zero
+        let centre = |sum_xy: f64, sum_x: f64, sum_y: f64, scale: f64| {
+            let value = sum_xy - sum_x * sum_y / n;
+            if value.abs() <= 1e-12 * scale {
+                0.0
+            } else {
+                value
+            }
+        };
+        CentredMoments {
+            mean_a: mean_a + self.anchor_a,
+            mean_b: mean_b + self.anchor_b,
+            s_aa: centre(self.sum_aa, self.sum_a, self.sum_a, self.sum_aa).max(0.0),
+            s_bb: centre(self.sum_bb, self.sum_b, self.sum_b, self.sum_bb).max(0.0),
+            s_ab: centre(self.sum_ab, self.sum_a, self.sum_b, (self.sum_aa * self.sum_bb).sqrt()),
+        }
+    }
+}
+
+/// Calls `visit` with the moments of bars `max(0, i - window + 1)..=i` for every
+/// bar `i`, so that early bars see the shorter windows available to them.
+pub(crate) fn for_each_window(prices_a: &[f64], prices_b: &[f64], window: usize, mut visit: impl FnMut(CentredMoments)) {
+    let mut moments = WindowMoments::new(prices_a[0], prices_b[0]);
+    for i in 0..prices_a.len() {
+        if i >= window && i % window == 0 {
+            // Recompute from scratch once per window length, anchored on the window, so that
+            // neither rounding from the updates nor drift away from the anchor builds up
+            let start = i + 1 - window;
+            moments = WindowMoments::from_window(&prices_a[start..=i], &prices_b[start..=i]);
+        } else {
+            if i >= window {
+                moments.pop(prices_a[i - window], prices_b[i - window]);
+            }
+            moments.push(prices_a[i], prices_b[i]);
+        }
+        visit(moments.centred());
+    }
+}
+
+/// Rolling z-scores with the worker's `calculateZScore` conventions.
+pub fn rolling_z_scores(data: &[f64], lookback: usize) -> Vec<f64> {
+    if lookback == 0 || data.len() < lookback {
+        return vec![0.0; data.len()];
+    }
+    (0..data.len())
+        .map(|i| {
+            if i + 1 < lookback {
+                return 0.0;
+            }
+            let window = &data[i + 1 - lookback..=i];
+            let mean = window.iter().sum::<f64>() / lookback as f64;
+            let variance = if lookback > 1 {
+                window.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (lookback - 1) as f64
+            } else {
+                0.0
+            };
+            let std_dev = variance.sqrt();
+            if std_dev > 0.0 {
+                (data[i] - mean) / std_dev
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// Checks that the two legs of a pair line up and are usable.
+pub(crate) fn validate_pair(prices_a: &[f64], prices_b: &[f64], required: usize) -> Result<(), Error> {
+    if prices_a.len() != prices_b.len() {
+        return Err(Error::InvalidOption(format!(
+            "both legs must have the same length ({} and {})",
+            prices_a.len(),
+            prices_b.len()
+        )));
+    }
+    error::validate_series(prices_a, required)?;
+    error::validate_series(prices_b, required)
+}
@@ -0,0 +1,356 @@
+//! Johansen (1991) cointegration test for baskets of 2 to 12 series, following statsmodels'
+//! `coint_johansen`. The reduced-rank regression of the VECM gives trace and maximum
+//! eigenvalue statistics for each hypothesised rank and the cointegrating vectors, which
+//! double as basket hedge weights.
+
+use nalgebra::{DMatrix, DVector};
+use wasm_bindgen::prelude::*;
+
+use crate::error::{self, Error};
+use crate::ols;
+use crate::regression::Regression;
+use crate::CriticalValues;
+
+/// Largest basket with tabulated critical values.
+pub const MAX_SERIES: usize = 12;
+
+/// Osterwald-Lenum style critical values as used by statsmodels (`c_sjt` and `c_sja`):
+/// row `k - r - 1` holds the 90%, 95% and 99% quantiles for `k - r` common trends.
+type JohansenTable = [[f64; 3]; MAX_SERIES];
+
+const TRACE_NO_CONSTANT: JohansenTable = [
+    [2.9762, 4.1296, 6.9406],
+    [10.4741, 12.3212, 16.3640],
+    [21.7781, 24.2761, 29.5147],
+    [37.0339, 40.1749, 46.5716],
+    [56.2839, 60.0627, 67.6367],
+    [79.5329, 83.9383, 92.7136],
+    [106.7351, 111.7797, 121.7375],
+    [137.9954, 143.6691, 154.7977],
+    [173.2292, 179.5199, 191.8122],
+    [212.4721, 219.4051, 232.8291],
+    [255.6732, 263.2603, 277.9962],
+    [302.9054, 311.1288, 326.9716],
+];
+
+const TRACE_CONSTANT: JohansenTable = [
+    [2.7055, 3.8415, 6.6349],
+    [13.4294, 15.4943, 19.9349],
+    [27.0669, 29.7961, 35.4628],
+    [44.4929, 47.8545, 54.6815],
+    [65.8202, 69.8189, 77.8202],
+    [91.1090, 95.7542, 104.9637],
+    [120.3673, 125.6185, 135.9825],
+    [153.6341, 159.5290, 171.0905],
+    [190.8714, 197.3772, 210.0366],
+    [232.1030, 239.2468, 253.2526],
+    [277.3740, 285.1402, 300.2821],
+    [326.5354, 334.9795, 351.2150],
+];
+
+const TRACE_TREND: JohansenTable = [
+    [2.7055, 3.8415, 6.6349],
+    [16.1619, 18.3985, 23.1485],
+    [32.0645, 35.0116, 41.0815],
+    [51.6492, 55.2459, 62.5202],
+    [75.1027, 79.3422, 87.7748],
+    [102.4674, 107.3429, 116.9829],
+    [133.7852, 139.2780, 150.0778],
+    [169.0618, 175.1584, 187.1891],
+    [208.3582, 215.1268, 228.2226],
+    [251.6293, 259.0267, 273.3838],
+    [298.8836, 306.8988, 322.4264],
+    [350.1125, 358.7190, 375.3203],
+];
+
+const MAX_EIGEN_NO_CONSTANT: JohansenTable = [
+    [2.9762, 4.1296, 6.9406],
+    [9.4748, 11.2246, 15.0923],
+    [15.7175, 17.7961, 22.2519],
+    [21.8370, 24.1592, 29.0609],
+    [27.9160, 30.4428, 35.7359],
+    [33.9271, 36.6301, 42.2333],
+    [39.9085, 42.7679, 48.6606],
+    [45.8930, 48.8795, 55.0335],
+    [51.8528, 54.9629, 61.3449],
+    [57.7954, 61.0404, 67.6415],
+    [63.7248, 67.0756, 73.8856],
+    [69.6513, 73.0946, 80.0937],
+];
+
+const MAX_EIGEN_CONSTANT: JohansenTable = [
+    [2.7055, 3.8415, 6.6349],
+    [12.2971, 14.2639, 18.5200],
+    [18.8928, 21.1314, 25.8650],
+    [25.1236, 27.5858, 32.7172],
+    [31.2379, 33.8777, 39.3693],
+    [37.2786, 40.0763, 45.8662],
+    [43.2947, 46.2299, 52.3069],
+    [49.2855, 52.3622, 58.6634],
+    [55.2412, 58.4332, 64.9960],
+    [61.2041, 64.5040, 71.2525],
+    [67.1307, 70.5392, 77.4877],
+    [73.0563, 76.5734, 83.7105],
+];
+
+const MAX_EIGEN_TREND: JohansenTable = [
+    [2.7055, 3.8415, 6.6349],
+    [15.0006, 17.1481, 21.7465],
+    [21.8731, 24.2522, 29.2631],
+    [28.2398, 30.8151, 36.1930],
+    [34.4202, 37.1646, 42.8612],
+    [40.5244, 43.4183, 49.4095],
+    [46.5583, 49.5875, 55.8171],
+    [52.5858, 55.7302, 62.1741],
+    [58.5316, 61.8051, 68.5030],
+    [64.5292, 67.9040, 74.7434],
+    [70.4630, 73.9355, 81.0678],
+    [76.4081, 79.9878, 87.2395],
+];
+
+fn tables(regression: Regression) -> (&'static JohansenTable, &'static JohansenTable) {
+    match regression {
+        Regression::NoConstant => (&TRACE_NO_CONSTANT, &MAX_EIGEN_NO_CONSTANT),
+        Regression::Constant => (&TRACE_CONSTANT, &MAX_EIGEN_CONSTANT),
+        _ => (&TRACE_TREND, &MAX_EIGEN_TREND),
+    }
+}
+
+fn table_critical_values(table: &JohansenTable, common_trends: usize) -> CriticalValues {
+    let [ten_percent, five_percent, one_percent] = table[common_trends - 1];
+    CriticalValues {
+        one_percent,
+        five_percent,
+        ten_percent,
+    }
+}
+
+#[wasm_bindgen]
+pub struct JohansenResult {
+    eigenvalues: Vec<f64>,
+    trace_statistics: Vec<f64>,
+    max_eigen_statistics: Vec<f64>,
+    trace_critical_values: Vec<CriticalValues>,
+    max_eigen_critical_values: Vec<CriticalValues>,
+    eigenvectors: Vec<Option<Vec<f64>>>,
+    /// Cointegrating rank from the sequential trace test at 5%
+    pub rank: u32,
+    /// Cointegrating rank from the sequential maximum eigenvalue test at 5%
+    pub max_eigen_rank: u32,
+    pub n_series: u32,
+    /// Observations in the reduced-rank regression
+    pub nobs: u32,
+}
+
+#[wasm_bindgen]
+impl JohansenResult {
+    /// Eigenvalues in decreasing order
+    #[wasm_bindgen(getter)]
+    pub fn eigenvalues(&self) -> Vec<f64> {
+        self.eigenvalues.clone()
+    }
+
+    /// Trace statistic for H0: rank <= r, for r = 0 .. n_series - 1
+    #[wasm_bindgen(getter)]
+    pub fn trace_statistics(&self) -> Vec<f64> {
+        self.trace_statistics.clone()
+    }
+
+    /// Maximum eigenvalue statistic for H0: rank = r against rank = r + 1
+    #[wasm_bindgen(getter)]
+    pub fn max_eigen_statistics(&self) -> Vec<f64> {
+        self.max_eigen_statistics.clone()
+    }
+
+    /// Critical values of each trace statistic, as an array of `{ "1%", "5%", "10%" }`
+    #[wasm_bindgen(getter)]
+    pub fn trace_critical_values(&self) -> JsValue {
+        to_js_array(&self.trace_critical_values)
+    }
+
+    /// Critical values of each maximum eigenvalue statistic
+    #[wasm_bindgen(getter)]
+    pub fn max_eigen_critical_values(&self) -> JsValue {
+        to_js_array(&self.max_eigen_critical_values)
+    }
+
+    /// Cointegrating vector for the `index`-th largest eigenvalue, scaled so that the
+    /// weight of the first series is 1: the spread is `sum(w[i] * series[i])`. `undefined`
+    /// when the vector leaves the first series out, so that it cannot be scaled that way.
+    pub fn hedge_weights(&self, index: usize) -> Option<Vec<f64>> {
+        self.eigenvectors.get(index).cloned().flatten()
+    }
+}
+
+impl JohansenResult {
+    pub fn trace_critical_value_table(&self) -> &[CriticalValues] {
+        &self.trace_critical_values
+    }
+
+    pub fn max_eigen_critical_value_table(&self) -> &[CriticalValues] {
+        &self.max_eigen_critical_values
+    }
+}
+
+fn to_js_array(critical_values: &[CriticalValues]) -> JsValue {
+    critical_values
+        .iter()
+        .map(|cv| cv.to_js())
+        .collect::<js_sys::Array>()
+        .into()
+}
+
+/// Johansen test on `n_series` series of equal length stored one after another in `data`.
+/// `regression` is the deterministic case ("n", "c" or "ct", statsmodels' `det_order`
+/// -1, 0 and 1) and `lags` the number of lagged differences in the VECM.
+#[wasm_bindgen]
+pub fn calculate_johansen_test(
+    data: Vec<f64>,
+    n_series: usize,
+    regression: &str,
+    lags: u32,
+) -> Result<JohansenResult, JsError> {
+    if n_series == 0 || !data.len().is_multiple_of(n_series) {
+        return Err(Error::InvalidOption(format!(
+            "{} values cannot be split into {} series of equal length",
+            data.len(),
+            n_series
+        ))
+        .into());
+    }
+    let length = data.len() / n_series;
+    let series: Vec<Vec<f64>> = data.chunks(length.max(1)).map(|c| c.to_vec()).collect();
+    Ok(johansen_test(&series, regression.parse()?, lags as usize)?)
+}
+
+pub fn johansen_test(series: &[Vec<f64>], regression: Regression, lags: usize) -> Result<JohansenResult, Error> {
+    let k = series.len();
+    if !(2..=MAX_SERIES).contains(&k) {
+        return Err(Error::InvalidOption(format!(
+            "the Johansen test needs between 2 and {} series, got {}",
+            MAX_SERIES, k
+        )));
+    }
+    if regression == Regression::ConstantTrendSquared {
+        return Err(Error::InvalidOption(
+            "the Johansen test supports the \"n\", \"c\" and \"ct\" cases".to_string(),
+        ));
+    }
+    let n = series[0].len();
+    if let Some(other) = series.iter().find(|s| s.len() != n) {
+        return Err(Error::InvalidOption(format!(
+            "all series must have the same length ({} and {})",
+            n,
+            other.len()
+        )));
+    }
+    // Enough rows for the lagged differences plus a few degrees of freedom
+    let required = lags + 2 + k * (lags + 1) + regression.n_terms() + 2;
+    for s in series {
+        error::validate_series(s, required)?;
+    }
+
+    // As in statsmodels, the levels are detrended with the requested order and the
+    // auxiliary regressions are demeaned whenever any deterministic term is present
+    let levels = DMatrix::from_fn(n, k, |i, j| series[j][i]);
+    let levels = detrend(levels, regression)?;
+    let auxiliary = match regression {
+        Regression::NoConstant => Regression::NoConstant,
+        _ => Regression::Constant,
+    };
+
+    let diff = DMatrix::from_fn(n - 1, k, |i, j| levels[(i + 1, j)] - levels[(i, j)]);
+    let t = n - 1 - lags;
+    let lagged_diffs = DMatrix::from_fn(t, k * lags, |i, c| diff[(i + lags - 1 - c / k, c % k)]);
+    let lagged_diffs = detrend(lagged_diffs, auxiliary)?;
+    let current_diffs = detrend(diff.rows(lags, t).into_owned(), auxiliary)?;
+    let lagged_levels = detrend(levels.rows(lags, t).into_owned(), auxiliary)?;
+
+    let r0 = residualize(current_diffs, &lagged_diffs)?;
+    let rk = residualize(lagged_levels, &lagged_diffs)?;
+
+    // The eigenproblem det(lambda S_kk - S_k0 S_00^-1 S_0k) = 0 with S_ij = R_i'R_j / t.
+    // Factorising R_k = Q_k R D (columns scaled to unit norm) and R_0 = Q_0 R_0' turns it
+    // into the singular values of Q_k'Q_0, the squared canonical correlations, without
+    // forming the moment matrices; the eigenvectors are D^-1 R^-1 times the left singular
+    // vectors.
+    let (qr_k, scales) = ols::scaled_qr(&rk)?;
+    let (qr_0, _) = ols::scaled_qr(&r0)?;
+    let svd = (qr_k.q().transpose() * qr_0.q()).svd(true, false);
+    let left = svd.u.ok_or(Error::SingularDesign)?;
+    let mut vectors = qr_k.r().solve_upper_triangular(&left).ok_or(Error::SingularDesign)?;
+    for (j, &scale) in scales.iter().enumerate() {
+        vectors.row_mut(j).scale_mut(1.0 / scale);
+    }
+    let squared: Vec<f64> = svd.singular_values.iter().map(|s| s * s).collect();
+
+    let mut order: Vec<usize> = (0..k).collect();
+    order.sort_by(|&a, &b| squared[b].total_cmp(&squared[a]));
+    let eigenvalues: Vec<f64> = order.iter().map(|&i| squared[i].clamp(0.0, 1.0 - f64::EPSILON)).collect();
+
+    let eigenvectors = order
+        .iter()
+        .map(|&i| normalize_on_first(&vectors.column(i).into_owned()))
+        .collect();
+
+    let (trace_table, max_eigen_table) = tables(regression);
+    let log_terms: Vec<f64> = eigenvalues.iter().map(|a| (1.0 - a).ln()).collect();
+    let trace_statistics: Vec<f64> = (0..k).map(|r| -(t as f64) * log_terms[r..].iter().sum::<f64>()).collect();
+    let max_eigen_statistics: Vec<f64> = log_terms.iter().map(|l| -(t as f64) * l).collect();
+    let trace_critical_values: Vec<CriticalValues> = (0..k).map(|r| table_critical_values(trace_table, k - r)).collect();
+    let max_eigen_critical_values: Vec<CriticalValues> =
+        (0..k).map(|r| table_critical_values(max_eigen_table, k - r)).collect();
+
+    Ok(JohansenResult {
+        rank: sequential_rank(&trace_statistics, &trace_critical_values) as u32,
+        max_eigen_rank: sequential_rank(&max_eigen_statistics, &max_eigen_critical_values) as u32,
+        eigenvalues,
+        trace_statistics,
+        max_eigen_statistics,
+        trace_critical_values,
+        max_eigen_critical_values,
+        eigenvectors,
+        n_series: k as u32,
+        nobs: t as u32,
+    })
+}
+
+/// `v / v[0]`, or `None` when the first weight is negligible next to the largest and the
+/// division would only amplify rounding.
+fn normalize_on_first(v: &DVector<f64>) -> Option<Vec<f64>> {
+    let largest = v.amax();
+    if v[0].is_nan() || v[0].abs() <= 1e-8 * largest {
+        return None;
+    }
+    Some(v.iter().map(|w| w / v[0]).collect())
+}
+
+/// The first r whose null is not rejected at 5%, or full rank when every null is rejected.
+fn sequential_rank(statistics: &[f64], critical_values: &[CriticalValues]) -> usize {
+    statistics
+        .iter()
+        .zip(critical_values)
+        .position(|(stat, cv)| *stat < cv.five_percent)
+        .unwrap_or(statistics.len())
+}
+
+/// Removes the deterministic terms of `regression` from every column.
+fn detrend(y: DMatrix<f64>, regression: Regression) -> Result<DMatrix<f64>, Error> {
+    if regression == Regression::NoConstant {
+        return Ok(y);
+    }
+    let design = regression.design(y.nrows());
+    residualize(y, &design)
+}
+
+/// Residuals of the least-squares projection of every column of `y` on `x`.
+fn residualize(mut y: DMatrix<f64>, x: &DMatrix<f64>) -> Result<DMatrix<f64>, Error> {
+    if x.ncols() == 0 {
+        return Ok(y);
+    }
+    for mut column in y.column_iter_mut() {
+        let fit = ols::ols(x, &column.clone_owned())?;
+        column.copy_from(&fit.residuals);
+    }
+    Ok(y)
+}
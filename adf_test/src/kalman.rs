@@ -0,0 +1,425 @@
+//! Kalman filter hedge ratio, ported from `kalmanFilter` in the calculations worker.
+//!
+//! The state is `[alpha, beta]`, a random walk with covariance `process_noise * I`, observed
+//! through `a_t = alpha_t + beta_t * b_t + e_t` with `Var(e_t) = measurement_noise`. The
+//! filter starts from an OLS fit on the first `initial_lookback` bars with covariance
+//! `1000 * I`, and those warm-up bars keep the OLS coefficients. At least one bar must follow
+//! the warm-up.
+//!
+//! Besides the filter the same model offers the RTS smoother and EM estimates of the two
+//! noise variances, so the pages no longer have to guess them.
+
+use nalgebra::{Matrix2, Vector2};
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::hedge::{self, HedgeSeries};
+
+/// Variance of the initial state around the warm-up OLS estimate.
+const INITIAL_STATE_VARIANCE: f64 = 1000.0;
+
+/// Process noise the pages default to, and where EM starts when given zero.
+pub const DEFAULT_PROCESS_NOISE: f64 = 1e-4;
+
+/// EM iterations used when none are given.
+pub const DEFAULT_EM_ITERATIONS: usize = 500;
+
+/// Relative change in log-likelihood below which EM stops.
+const EM_TOLERANCE: f64 = 1e-9;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct KalmanHedge {
+    process_noise: f64,
+    measurement_noise: Option<f64>,
+    initial_lookback: usize,
+}
+
+#[wasm_bindgen]
+impl KalmanHedge {
+    /// `measurement_noise` left `undefined` uses the residual variance of the warm-up OLS
+    /// fit, which is what the worker has always done.
+    #[wasm_bindgen(constructor)]
+    pub fn new(process_noise: f64, measurement_noise: Option<f64>, initial_lookback: usize) -> KalmanHedge {
+        KalmanHedge {
+            process_noise,
+            measurement_noise,
+            initial_lookback,
+        }
+    }
+
+    /// Filters the pair of close prices; throws on mismatched, short or non-finite input
+    pub fn run(&self, prices_a: Vec<f64>, prices_b: Vec<f64>) -> Result<KalmanHedgeResult, JsError> {
+        Ok(self.filter(&prices_a, &prices_b)?)
+    }
+
+    /// Smoothed coefficients over the whole pair, for after-the-fact analysis
+    pub fn run_smoother(&self, prices_a: Vec<f64>, prices_b: Vec<f64>) -> Result<KalmanSmootherResult, JsError> {
+        Ok(self.smooth(&prices_a, &prices_b)?)
+    }
+
+    /// Maximum-likelihood noise settings for the pair, starting from this filter's
+    pub fn fit_noise(&self, prices_a: Vec<f64>, prices_b: Vec<f64>, max_iterations: Option<u32>) -> Result<KalmanNoiseEstimate, JsError> {
+        let max_iterations = max_iterations.map_or(DEFAULT_EM_ITERATIONS, |n| n as usize);
+        Ok(self.estimate_noise(&prices_a, &prices_b, max_iterations)?)
+    }
+}
+
+#[wasm_bindgen]
+pub struct KalmanHedgeResult {
+    series: HedgeSeries,
+    innovations: Vec<f64>,
+    innovation_variances: Vec<f64>,
+    /// Measurement noise the filter ran with
+    pub measurement_noise: f64,
+    /// Gaussian log-likelihood of the innovations after the warm-up
+    pub log_likelihood: f64,
+}
+
+#[wasm_bindgen]
+impl KalmanHedgeResult {
+    /// Filtered alpha, beta and spread for each bar
+    #[wasm_bindgen(getter)]
+    pub fn series(&self) -> HedgeSeries {
+        self.series.clone()
+    }
+
+    /// One-step-ahead prediction errors `a_t - (alpha_{t|t-1} + beta_{t|t-1} * b_t)`; NaN
+    /// during the warm-up
+    #[wasm_bindgen(getter)]
+    pub fn innovations(&self) -> Vec<f64> {
+        self.innovations.clone()
+    }
+
+    /// Variance of each innovation; NaN during the warm-up
+    #[wasm_bindgen(getter)]
+    pub fn innovation_variances(&self) -> Vec<f64> {
+        self.innovation_variances.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub struct KalmanSmootherResult {
+    series: HedgeSeries,
+    alpha_std_errors: Vec<f64>,
+    beta_std_errors: Vec<f64>,
+    pub measurement_noise: f64,
+    /// Log-likelihood of the forward pass, as in [`KalmanHedgeResult`]
+    pub log_likelihood: f64,
+}
+
+#[wasm_bindgen]
+impl KalmanSmootherResult {
+    /// Smoothed alpha, beta and spread for each bar; the warm-up keeps the OLS fit
+    #[wasm_bindgen(getter)]
+    pub fn series(&self) -> HedgeSeries {
+        self.series.clone()
+    }
+
+    /// Posterior standard deviation of each smoothed alpha; NaN during the warm-up
+    #[wasm_bindgen(getter)]
+    pub fn alpha_std_errors(&self) -> Vec<f64> {
+        self.alpha_std_errors.clone()
+    }
+
+    /// Posterior standard deviation of each smoothed beta; NaN during the warm-up
+    #[wasm_bindgen(getter)]
+    pub fn beta_std_errors(&self) -> Vec<f64> {
+        self.beta_std_errors.clone()
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct KalmanNoiseEstimate {
+    pub process_noise: f64,
+    pub measurement_noise: f64,
+    /// Log-likelihood at the estimated noise
+    pub log_likelihood: f64,
+    pub iterations: u32,
+    /// False when EM hit the iteration limit first
+    pub converged: bool,
+    pub initial_lookback: u32,
+}
+
+#[wasm_bindgen]
+impl KalmanNoiseEstimate {
+    /// A filter configured with the estimated noise
+    pub fn hedge(&self) -> KalmanHedge {
+        KalmanHedge::new(self.process_noise, Some(self.measurement_noise), self.initial_lookback as usize)
+    }
+}
+
+impl KalmanHedge {
+    pub fn process_noise(&self) -> f64 {
+        self.process_noise
+    }
+
+    pub fn measurement_noise(&self) -> Option<f64> {
+        self.measurement_noise
+    }
+
+    pub fn initial_lookback(&self) -> usize {
+        self.initial_lookback
+    }
+
+    pub fn filter(&self, prices_a: &[f64], prices_b: &[f64]) -> Result<KalmanHedgeResult, Error> {
+        self.validate(prices_a, prices_b)?;
+        let warm_up = WarmUp::fit(prices_a, prices_b, self.initial_lookback);
+        let r = self.measurement_noise.unwrap_or(warm_up.residual_variance);
+        let pass = FilterPass::run(prices_a, prices_b, &warm_up, self.process_noise, r);
+
+        let lookback = self.initial_lookback;
+        let mut alphas = vec![warm_up.alpha; lookback];
+        let mut betas = vec![warm_up.beta; lookback];
+        alphas.extend(pass.states[1..].iter().map(|x| x[0]));
+        betas.extend(pass.states[1..].iter().map(|x| x[1]));
+        let mut innovations = vec![f64::NAN; lookback];
+        let mut innovation_variances = vec![f64::NAN; lookback];
+        innovations.extend_from_slice(&pass.innovations);
+        innovation_variances.extend_from_slice(&pass.innovation_variances);
+
+        Ok(KalmanHedgeResult {
+            series: HedgeSeries::new(prices_a, prices_b, alphas, betas),
+            innovations,
+            innovation_variances,
+            measurement_noise: r,
+            log_likelihood: pass.log_likelihood,
+        })
+    }
+
+    /// Rauch-Tung-Striebel smoothed coefficients: each bar's alpha and beta given the whole
+    /// sample rather than only the bars up to it.
+    pub fn smooth(&self, prices_a: &[f64], prices_b: &[f64]) -> Result<KalmanSmootherResult, Error> {
+        self.validate(prices_a, prices_b)?;
+        let warm_up = WarmUp::fit(prices_a, prices_b, self.initial_lookback);
+        let r = self.measurement_noise.unwrap_or(warm_up.residual_variance);
+        let pass = FilterPass::run(prices_a, prices_b, &warm_up, self.process_noise, r);
+        let smoothed = pass.smooth()?;
+
+        let lookback = self.initial_lookback;
+        let mut alphas = vec![warm_up.alpha; lookback];
+        let mut betas = vec![warm_up.beta; lookback];
+        let mut alpha_std_errors = vec![f64::NAN; lookback];
+        let mut beta_std_errors = vec![f64::NAN; lookback];
+        for (x, p) in smoothed.states[1..].iter().zip(&smoothed.covariances[1..]) {
+            alphas.push(x[0]);
+            betas.push(x[1]);
+            alpha_std_errors.push(p[(0, 0)].max(0.0).sqrt());
+            beta_std_errors.push(p[(1, 1)].max(0.0).sqrt());
+        }
+
+        Ok(KalmanSmootherResult {
+            series: HedgeSeries::new(prices_a, prices_b, alphas, betas),
+            alpha_std_errors,
+            beta_std_errors,
+            measurement_noise: r,
+            log_likelihood: pass.log_likelihood,
+        })
+    }
+
+    /// Maximum-likelihood process and measurement noise by EM (Shumway and Stoffer, 1982),
+    /// starting from this filter's settings. The warm-up OLS fit stays the initial state, so
+    /// only the bars after it enter the likelihood.
+    pub fn estimate_noise(&self, prices_a: &[f64], prices_b: &[f64], max_iterations: usize) -> Result<KalmanNoiseEstimate, Error> {
+        self.validate(prices_a, prices_b)?;
+        let warm_up = WarmUp::fit(prices_a, prices_b, self.initial_lookback);
+        let mut q = self.process_noise;
+        let mut r = self.measurement_noise.unwrap_or(warm_up.residual_variance);
+        if q == 0.0 {
+            // EM cannot move a variance away from zero
+            q = DEFAULT_PROCESS_NOISE;
+        }
+
+        let observed_a = &prices_a[self.initial_lookback..];
+        let observed_b = &prices_b[self.initial_lookback..];
+        let m = observed_a.len() as f64;
+        let mut previous = f64::NEG_INFINITY;
+        let mut iterations = 0;
+        let mut converged = false;
+        let log_likelihood = loop {
+            let pass = FilterPass::run(prices_a, prices_b, &warm_up, q, r);
+            let log_likelihood = pass.log_likelihood;
+            if (log_likelihood - previous).abs() <= EM_TOLERANCE * (1.0 + log_likelihood.abs()) {
+                converged = true;
+                break log_likelihood;
+            }
+            if iterations == max_iterations {
+                break log_likelihood;
+            }
+            previous = log_likelihood;
+            iterations += 1;
+
+            let smoothed = pass.smooth()?;
+            let mut transition_sum = 0.0;
+            let mut measurement_sum = 0.0;
+            for k in 1..smoothed.states.len() {
+                let step = smoothed.states[k] - smoothed.states[k - 1];
+                transition_sum += step.norm_squared() + smoothed.covariances[k].trace() + smoothed.covariances[k - 1].trace()
+                    - 2.0 * smoothed.lag_covariances[k].trace();
+                let h = Vector2::new(1.0, observed_b[k - 1]);
+                let residual = observed_a[k - 1] - h.dot(&smoothed.states[k]);
+                measurement_sum += residual * residual + h.dot(&(smoothed.covariances[k] * h));
+            }
+            // Q is constrained to q * I, so its update averages the two diagonal terms
+            q = (transition_sum / (2.0 * m)).max(f64::MIN_POSITIVE);
+            r = (measurement_sum / m).max(f64::MIN_POSITIVE);
+        };
+
+        Ok(KalmanNoiseEstimate {
+            process_noise: q,
+            measurement_noise: r,
+            log_likelihood,
+            iterations: iterations as u32,
+            converged,
+            initial_lookback: self.initial_lookback as u32,
+        })
+    }
+
+    fn validate(&self, prices_a: &[f64], prices_b: &[f64]) -> Result<(), Error> {
+        if self.initial_lookback < 3 {
+            return Err(Error::InvalidOption(format!(
+                "initial_lookback must be at least 3, got {}",
+                self.initial_lookback
+            )));
+        }
+        if !(self.process_noise >= 0.0 && self.process_noise.is_finite()) {
+            return Err(Error::InvalidOption(format!(
+                "process_noise must be a non-negative number, got {}",
+                self.process_noise
+            )));
+        }
+        if let Some(r) = self.measurement_noise {
+            if !(r > 0.0 && r.is_finite()) {
+                return Err(Error::InvalidOption(format!(
+                    "measurement_noise must be positive, got {}",
+                    r
+                )));
+            }
+        }
+        // At least one bar after the warm-up, or there is nothing to filter
+        hedge::validate_pair(prices_a, prices_b, self.initial_lookback + 1)
+    }
+}
+
+/// OLS of `a` on `b` over the warm-up bars, with the worker's fallback of beta = 1 when `b`
+/// does not vary.
+struct WarmUp {
+    alpha: f64,
+    beta: f64,
+    residual_variance: f64,
+    lookback: usize,
+}
+
+impl WarmUp {
+    fn fit(prices_a: &[f64], prices_b: &[f64], lookback: usize) -> Self {
+        let (prices_a, prices_b) = (&prices_a[..lookback], &prices_b[..lookback]);
+        let n = lookback as f64;
+        let (mut sum_a, mut sum_b, mut sum_ab, mut sum_b2) = (0.0, 0.0, 0.0, 0.0);
+        for (a, b) in prices_a.iter().zip(prices_b) {
+            sum_a += a;
+            sum_b += b;
+            sum_ab += a * b;
+            sum_b2 += b * b;
+        }
+        let numerator = n * sum_ab - sum_a * sum_b;
+        let denominator = n * sum_b2 - sum_b * sum_b;
+        let beta = if denominator.abs() > 1e-10 { numerator / denominator } else { 1.0 };
+        let alpha = sum_a / n - beta * sum_b / n;
+
+        let rss: f64 = prices_a
+            .iter()
+            .zip(prices_b)
+            .map(|(a, b)| (a - (alpha + beta * b)).powi(2))
+            .sum();
+        WarmUp {
+            alpha,
+            beta,
+            residual_variance: rss / (n - 2.0),
+            lookback,
+        }
+    }
+}
+
+/// One forward pass over the bars after the warm-up. Index 0 of the state vectors is the
+/// initial state and index `k` the state after filtering bar `lookback + k - 1`.
+struct FilterPass {
+    states: Vec<Vector2<f64>>,
+    covariances: Vec<Matrix2<f64>>,
+    /// `P_{k|k-1}`; index 0 is unused
+    predicted_covariances: Vec<Matrix2<f64>>,
+    innovations: Vec<f64>,
+    innovation_variances: Vec<f64>,
+    log_likelihood: f64,
+}
+
+struct SmoothedStates {
+    states: Vec<Vector2<f64>>,
+    covariances: Vec<Matrix2<f64>>,
+    /// `Cov(x_k, x_{k-1})` given the whole sample; index 0 is unused
+    lag_covariances: Vec<Matrix2<f64>>,
+}
+
+impl FilterPass {
+    fn run(prices_a: &[f64], prices_b: &[f64], warm_up: &WarmUp, process_noise: f64, r: f64) -> Self {
+        let steps = prices_a.len() - warm_up.lookback;
+        let mut pass = FilterPass {
+            states: Vec::with_capacity(steps + 1),
+            covariances: Vec::with_capacity(steps + 1),
+            predicted_covariances: Vec::with_capacity(steps + 1),
+            innovations: Vec::with_capacity(steps),
+            innovation_variances: Vec::with_capacity(steps),
+            log_likelihood: 0.0,
+        };
+
+        let q = Matrix2::identity() * process_noise;
+        let mut x = Vector2::new(warm_up.alpha, warm_up.beta);
+        let mut p = Matrix2::identity() * INITIAL_STATE_VARIANCE;
+        pass.states.push(x);
+        pass.covariances.push(p);
+        pass.predicted_covariances.push(p);
+        for t in warm_up.lookback..prices_a.len() {
+            // The state is a random walk, so the prediction keeps x and inflates P
+            let p_pred = p + q;
+            let h = Vector2::new(1.0, prices_b[t]);
+            let innovation = prices_a[t] - h.dot(&x);
+            let p_h = p_pred * h;
+            let s = h.dot(&p_h) + r;
+            // Same guard as the worker's `scalarInverse`
+            let s_inv = if s.abs() < 1e-10 { 1.0 } else { 1.0 / s };
+            let gain = p_h * s_inv;
+
+            x += gain * innovation;
+            p = (Matrix2::identity() - gain * h.transpose()) * p_pred;
+
+            pass.log_likelihood -= 0.5 * ((2.0 * std::f64::consts::PI * s).ln() + innovation * innovation / s);
+            pass.states.push(x);
+            pass.covariances.push(p);
+            pass.predicted_covariances.push(p_pred);
+            pass.innovations.push(innovation);
+            pass.innovation_variances.push(s);
+        }
+        pass
+    }
+
+    /// Rauch-Tung-Striebel backward recursion, including the lag-one covariances EM needs.
+    fn smooth(&self) -> Result<SmoothedStates, Error> {
+        let n = self.states.len();
+        let mut states = self.states.clone();
+        let mut covariances = self.covariances.clone();
+        let mut lag_covariances = vec![Matrix2::zeros(); n];
+        for k in (0..n - 1).rev() {
+            let predicted_inv = self.predicted_covariances[k + 1].try_inverse().ok_or(Error::SingularDesign)?;
+            let j = self.covariances[k] * predicted_inv;
+            // With a random-walk state the one-step prediction of x_{k+1} is x_{k|k}
+            states[k] = self.states[k] + j * (states[k + 1] - self.states[k]);
+            covariances[k] = self.covariances[k] + j * (covariances[k + 1] - self.predicted_covariances[k + 1]) * j.transpose();
+            lag_covariances[k + 1] = covariances[k + 1] * j.transpose();
+        }
+        Ok(SmoothedStates {
+            states,
+            covariances,
+            lag_covariances,
+        })
+    }
+}
@@ -0,0 +1,110 @@
+//! Kwiatkowski-Phillips-Schmidt-Shin (1992) test. Its null hypothesis is stationarity,
+//! the reverse of ADF's, so running both separates "mean reverting" from "not enough
+//! evidence either way".
+
+use nalgebra::DVector;
+use wasm_bindgen::prelude::*;
+
+use crate::error::{self, Error};
+use crate::long_run_variance::{self, Bandwidth};
+use crate::ols;
+use crate::regression::Regression;
+use crate::CriticalValues;
+
+/// Levels of the tabulated critical values, from Table 1 of KPSS (1992).
+const P_VALUES: [f64; 4] = [0.10, 0.05, 0.025, 0.01];
+const LEVEL_CRITICAL_VALUES: [f64; 4] = [0.347, 0.463, 0.574, 0.739];
+const TREND_CRITICAL_VALUES: [f64; 4] = [0.119, 0.146, 0.176, 0.216];
+
+#[wasm_bindgen]
+pub struct KpssResult {
+    pub test_statistic: f64,
+    /// Newey-West bandwidth used for the long-run variance
+    pub lags: u32,
+    /// Interpolated from the KPSS table, so clipped to the range [0.01, 0.10]
+    pub p_value: f64,
+    critical_values: CriticalValues,
+    /// Stationarity is not rejected at the 5% level
+    pub is_stationary: bool,
+}
+
+#[wasm_bindgen]
+impl KpssResult {
+    #[wasm_bindgen(getter)]
+    pub fn critical_values(&self) -> JsValue {
+        self.critical_values.to_js()
+    }
+}
+
+impl KpssResult {
+    pub fn critical_value_table(&self) -> CriticalValues {
+        self.critical_values
+    }
+}
+
+/// KPSS test around a level ("c") or a linear trend ("ct"). `nlags` fixes the Newey-West
+/// bandwidth; leave it `undefined` for automatic selection.
+#[wasm_bindgen]
+pub fn calculate_kpss_test(data: Vec<f64>, regression: &str, nlags: Option<u32>) -> Result<KpssResult, JsError> {
+    Ok(kpss_test(&data, regression.parse()?, nlags.into())?)
+}
+
+pub fn kpss_test(data: &[f64], regression: Regression, bandwidth: Bandwidth) -> Result<KpssResult, Error> {
+    let table = match regression {
+        Regression::Constant => LEVEL_CRITICAL_VALUES,
+        Regression::ConstantTrend => TREND_CRITICAL_VALUES,
+        other => {
+            return Err(Error::InvalidOption(format!(
+                "KPSS supports the \"c\" and \"ct\" regressions, not \"{}\"",
+                other
+            )))
+        }
+    };
+    error::validate_series(data, regression.n_terms() + 4)?;
+
+    let nobs = data.len();
+    let y = DVector::from_column_slice(data);
+    let fit = ols::ols(&regression.design(nobs), &y)?;
+    let residuals = fit.residuals.as_slice();
+
+    let lags = bandwidth.lags(residuals);
+    let long_run_variance = long_run_variance::newey_west(residuals, lags);
+    let mut partial_sum = 0.0;
+    let eta: f64 = residuals
+        .iter()
+        .map(|e| {
+            partial_sum += e;
+            partial_sum * partial_sum
+        })
+        .sum();
+    let test_statistic = eta / ((nobs * nobs) as f64 * long_run_variance);
+
+    let p_value = interpolate_p_value(test_statistic, &table);
+    let critical_values = CriticalValues {
+        one_percent: table[3],
+        five_percent: table[1],
+        ten_percent: table[0],
+    };
+
+    Ok(KpssResult {
+        test_statistic,
+        lags: lags as u32,
+        p_value,
+        critical_values,
+        is_stationary: test_statistic < critical_values.five_percent,
+    })
+}
+
+/// Linear interpolation in the critical value table, clipped at its ends.
+fn interpolate_p_value(test_statistic: f64, table: &[f64; 4]) -> f64 {
+    if test_statistic <= table[0] {
+        return P_VALUES[0];
+    }
+    if test_statistic >= table[3] {
+        return P_VALUES[3];
+    }
+    let k = table.iter().rposition(|&cv| cv <= test_statistic).unwrap_or(0);
+    let (x1, x2) = (table[k], table[k + 1]);
+    let (y1, y2) = (P_VALUES[k], P_VALUES[k + 1]);
+    y1 + (test_statistic - x1) * (y2 - y1) / (x2 - x1)
+}
@@ -0,0 +1,125 @@
+//! Lag-length selection for augmented Dickey-Fuller style regressions, matching the
+//! `autolag` options of statsmodels' `adfuller`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::Error;
+
+/// 95th percentile of the standard normal, the stopping rule of the t-stat search.
+const T_STAT_STOP: f64 = 1.6448536269514722;
+
+/// How the number of lagged differences is chosen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LagCriterion {
+    /// Minimise the Akaike information criterion
+    #[default]
+    Aic,
+    /// Minimise the Schwarz (Bayesian) information criterion
+    Bic,
+    /// Minimise the Hannan-Quinn information criterion
+    Hqic,
+    /// General-to-specific: starting from the maximum lag, keep the first lag whose
+    /// coefficient is significant at the 5% level (|t| >= 1.645)
+    TStat,
+    /// Use the maximum lag as given
+    Fixed,
+}
+
+impl LagCriterion {
+    pub fn code(self) -> &'static str {
+        match self {
+            LagCriterion::Aic => "aic",
+            LagCriterion::Bic => "bic",
+            LagCriterion::Hqic => "hqic",
+            LagCriterion::TStat => "t-stat",
+            LagCriterion::Fixed => "fixed",
+        }
+    }
+}
+
+impl FromStr for LagCriterion {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "aic" => Ok(LagCriterion::Aic),
+            "bic" => Ok(LagCriterion::Bic),
+            "hqic" | "hq" => Ok(LagCriterion::Hqic),
+            "t-stat" | "tstat" => Ok(LagCriterion::TStat),
+            "fixed" | "none" => Ok(LagCriterion::Fixed),
+            other => Err(Error::InvalidOption(format!(
+                "unknown autolag \"{}\" (expected one of \"aic\", \"bic\", \"hqic\", \"t-stat\", \"fixed\")",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for LagCriterion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Schwert's (1989) rule of thumb for the maximum lag, `ceil(12 * (nobs / 100)^(1/4))`.
+pub fn schwert_max_lag(nobs: usize) -> usize {
+    (12.0 * (nobs as f64 / 100.0).powf(0.25)).ceil() as usize
+}
+
+/// Gaussian log-likelihood of a regression with the given sum of squared residuals.
+pub fn log_likelihood(ssr: f64, nobs: usize) -> f64 {
+    let n = nobs as f64;
+    -n / 2.0 * ((2.0 * std::f64::consts::PI).ln() + (ssr / n).ln() + 1.0)
+}
+
+pub fn aic(ssr: f64, nobs: usize, n_params: usize) -> f64 {
+    -2.0 * log_likelihood(ssr, nobs) + 2.0 * n_params as f64
+}
+
+pub fn bic(ssr: f64, nobs: usize, n_params: usize) -> f64 {
+    -2.0 * log_likelihood(ssr, nobs) + (nobs as f64).ln() * n_params as f64
+}
+
+pub fn hqic(ssr: f64, nobs: usize, n_params: usize) -> f64 {
+    -2.0 * log_likelihood(ssr, nobs) + 2.0 * (nobs as f64).ln().ln() * n_params as f64
+}
+
+/// One fitted candidate in a lag search.
+#[derive(Clone, Copy, Debug)]
+pub struct LagCandidate {
+    pub lags: usize,
+    pub ssr: f64,
+    pub nobs: usize,
+    pub n_params: usize,
+    /// t-statistic of the coefficient on the longest lagged difference (unused when `lags` is 0)
+    pub last_lag_t: f64,
+}
+
+/// Picks the lag length from candidates fitted for every lag in `0..=max_lag`. Candidates
+/// that could not be estimated are simply absent. Returns `None` if nothing was fitted.
+pub fn select_lag(criterion: LagCriterion, candidates: &[LagCandidate]) -> Option<usize> {
+    let by_criterion = |ic: fn(f64, usize, usize) -> f64| {
+        candidates
+            .iter()
+            .map(|c| (c.lags, ic(c.ssr, c.nobs, c.n_params)))
+            .filter(|(_, value)| value.is_finite())
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(lags, _)| lags)
+    };
+
+    match criterion {
+        LagCriterion::Aic => by_criterion(aic),
+        LagCriterion::Bic => by_criterion(bic),
+        LagCriterion::Hqic => by_criterion(hqic),
+        LagCriterion::TStat => {
+            let mut ordered: Vec<&LagCandidate> = candidates.iter().collect();
+            ordered.sort_by_key(|c| std::cmp::Reverse(c.lags));
+            ordered
+                .iter()
+                .find(|c| c.lags == 0 || c.last_lag_t.abs() >= T_STAT_STOP)
+                .map(|c| c.lags)
+        }
+        LagCriterion::Fixed => candidates.iter().map(|c| c.lags).max(),
+    }
+}
@@ -0,0 +1,67 @@
+//! Newey-West (Bartlett kernel) long-run variance estimation, shared by the KPSS and
+//! Phillips-Perron tests.
+
+/// Long-run variance of `residuals` with a Bartlett kernel and bandwidth `lags`:
+/// `(sum e_t^2 + 2 sum_{i=1..lags} (1 - i / (lags + 1)) sum_t e_t e_{t-i}) / n`.
+pub fn newey_west(residuals: &[f64], lags: usize) -> f64 {
+    let nobs = residuals.len();
+    let mut s_hat: f64 = residuals.iter().map(|e| e * e).sum();
+    for i in 1..=lags.min(nobs.saturating_sub(1)) {
+        let autocovariance: f64 = residuals[i..].iter().zip(residuals).map(|(a, b)| a * b).sum();
+        s_hat += 2.0 * autocovariance * (1.0 - i as f64 / (lags as f64 + 1.0));
+    }
+    s_hat / nobs as f64
+}
+
+/// Data-dependent bandwidth of Hobijn, Franses and Ooms (1998), as used by statsmodels'
+/// `kpss(nlags="auto")`.
+pub fn hobijn_bandwidth(residuals: &[f64]) -> usize {
+    let nobs = residuals.len();
+    let n = nobs as f64;
+    let covlags = n.powf(2.0 / 9.0) as usize;
+    let mut s0 = residuals.iter().map(|e| e * e).sum::<f64>() / n;
+    let mut s1 = 0.0;
+    for i in 1..=covlags.min(nobs.saturating_sub(1)) {
+        let product: f64 = residuals[i..].iter().zip(residuals).map(|(a, b)| a * b).sum::<f64>() / (n / 2.0);
+        s0 += product;
+        s1 += i as f64 * product;
+    }
+    let s_hat = s1 / s0;
+    let gamma_hat = 1.1447 * (s_hat * s_hat).powf(1.0 / 3.0);
+    (gamma_hat * n.powf(1.0 / 3.0)) as usize
+}
+
+/// The fixed rule `ceil(12 * (nobs / 100)^(1/4))`.
+pub fn schwert_bandwidth(nobs: usize) -> usize {
+    crate::lag_selection::schwert_max_lag(nobs)
+}
+
+/// How the Newey-West bandwidth is chosen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Bandwidth {
+    /// Hobijn, Franses and Ooms (1998) data-dependent selection
+    #[default]
+    Auto,
+    /// `ceil(12 * (nobs / 100)^(1/4))`
+    Schwert,
+    Fixed(usize),
+}
+
+impl Bandwidth {
+    /// Number of autocovariance lags to use for `residuals`, never more than `n - 1`.
+    pub fn lags(self, residuals: &[f64]) -> usize {
+        let lags = match self {
+            Bandwidth::Auto => hobijn_bandwidth(residuals),
+            Bandwidth::Schwert => schwert_bandwidth(residuals.len()),
+            Bandwidth::Fixed(lags) => lags,
+        };
+        lags.min(residuals.len().saturating_sub(1))
+    }
+}
+
+impl From<Option<u32>> for Bandwidth {
+    /// `None` selects the bandwidth automatically, `Some(lags)` fixes it.
+    fn from(lags: Option<u32>) -> Self {
+        lags.map_or(Bandwidth::Auto, |lags| Bandwidth::Fixed(lags as usize))
+    }
+}
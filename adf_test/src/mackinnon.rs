@@ -0,0 +1,221 @@
+//! MacKinnon (1994) approximate p-values and MacKinnon (2010) critical values for
+//! Dickey-Fuller tau statistics, following statsmodels' `adfvalues`.
+//!
+//! As in statsmodels, p-values come from the asymptotic surface of [`mackinnonp`] while
+//! [`critical_values`] evaluate the 2010 response surfaces at the number of observations,
+//! so on short windows a statistic at the 5% critical value need not have p = 0.05.
+//! There is no finite-sample p-value: MacKinnon (2010) only publishes surfaces for the
+//! three critical values, and the full quantile tables of MacKinnon (1996) are not
+//! bundled. Stationarity decisions therefore rest on the critical values alone.
+//!
+//! The `cointegration_*` functions cover residual-based tests on `n_variables` series
+//! (MacKinnon's N). Only N = 1 and N = 2, the pairs case, are tabulated, and N = 2 has no
+//! "n" specification.
+
+use crate::distributions::norm_cdf;
+use crate::error::Error;
+use crate::regression::Regression;
+use crate::CriticalValues;
+
+// Scaling applied to the published polynomial coefficients.
+const SMALL_SCALING: [f64; 3] = [1.0, 1.0, 1e-2];
+const LARGE_SCALING: [f64; 4] = [1.0, 1e-1, 1e-1, 1e-2];
+
+/// Coefficients of the p-value approximation for one regression specification.
+struct TauCoefficients {
+    /// Below `min` the p-value is reported as 0, above `max` as 1.
+    min: f64,
+    max: f64,
+    /// Cut-off between the small-p and large-p polynomials.
+    star: f64,
+    small_p: [f64; 3],
+    large_p: [f64; 4],
+}
+
+fn tau_coefficients(regression: Regression) -> TauCoefficients {
+    match regression {
+        Regression::NoConstant => TauCoefficients {
+            min: -19.04,
+            max: f64::INFINITY,
+            star: -1.04,
+            small_p: [0.6344, 1.2378, 3.2496],
+            large_p: [0.4797, 9.3557, -0.6999, 3.3066],
+        },
+        Regression::Constant => TauCoefficients {
+            min: -18.83,
+            max: 2.74,
+            star: -1.61,
+            small_p: [2.1659, 1.4412, 3.8269],
+            large_p: [1.7339, 9.3202, -1.2745, -1.0368],
+        },
+        Regression::ConstantTrend => TauCoefficients {
+            min: -16.18,
+            max: 0.7,
+            star: -2.89,
+            small_p: [3.2512, 1.6047, 4.9588],
+            large_p: [2.5261, 6.1654, -3.7956, -6.0285],
+        },
+        Regression::ConstantTrendSquared => TauCoefficients {
+            min: -17.17,
+            max: 0.54,
+            star: -3.21,
+            small_p: [4.0003, 1.6580, 4.8288],
+            large_p: [3.0778, 4.9529, -4.1477, -5.9359],
+        },
+    }
+}
+
+/// Coefficients for the residuals of a regression of one series on another (N = 2).
+fn pair_tau_coefficients(regression: Regression) -> Option<TauCoefficients> {
+    match regression {
+        Regression::NoConstant => None,
+        Regression::Constant => Some(TauCoefficients {
+            min: -18.86,
+            max: 0.92,
+            star: -2.62,
+            small_p: [2.9200, 1.5012, 3.9796],
+            large_p: [2.1945, 6.4695, -2.9198, -4.2377],
+        }),
+        Regression::ConstantTrend => Some(TauCoefficients {
+            min: -21.15,
+            max: 0.63,
+            star: -3.19,
+            small_p: [3.6646, 1.5419, 3.6448],
+            large_p: [2.8500, 5.2720, -3.6622, -5.1695],
+        }),
+        Regression::ConstantTrendSquared => Some(TauCoefficients {
+            min: -21.1,
+            max: 0.79,
+            star: -3.51,
+            small_p: [4.3534, 1.6016, 3.7947],
+            large_p: [3.4713, 5.9670, -3.2507, -4.2286],
+        }),
+    }
+}
+
+/// Asymptotic p-value of a Dickey-Fuller tau statistic for the given deterministic terms.
+pub fn mackinnonp(test_statistic: f64, regression: Regression) -> f64 {
+    tau_p_value(test_statistic, &tau_coefficients(regression))
+}
+
+fn tau_p_value(test_statistic: f64, coefficients: &TauCoefficients) -> f64 {
+    if test_statistic > coefficients.max {
+        return 1.0;
+    }
+    if test_statistic < coefficients.min {
+        return 0.0;
+    }
+
+    let z = if test_statistic <= coefficients.star {
+        polyval(&coefficients.small_p, &SMALL_SCALING, test_statistic)
+    } else {
+        polyval(&coefficients.large_p, &LARGE_SCALING, test_statistic)
+    };
+    norm_cdf(z)
+}
+
+/// MacKinnon (2010) response surfaces for the 1%, 5% and 10% critical values:
+/// `cv(T) = b0 + b1 / T + b2 / T^2 + b3 / T^3`.
+fn tau_2010(regression: Regression) -> [[f64; 4]; 3] {
+    match regression {
+        Regression::NoConstant => [
+            [-2.56574, -2.2358, -3.627, 0.0],
+            [-1.94100, -0.2686, -3.365, 31.223],
+            [-1.61682, 0.2656, -2.714, 25.364],
+        ],
+        Regression::Constant => [
+            [-3.43035, -6.5393, -16.786, -79.433],
+            [-2.86154, -2.8903, -4.234, -40.040],
+            [-2.56677, -1.5384, -2.809, 0.0],
+        ],
+        Regression::ConstantTrend => [
+            [-3.95877, -9.0531, -28.428, -134.155],
+            [-3.41049, -4.3904, -9.036, -45.374],
+            [-3.12705, -2.5856, -3.925, -22.380],
+        ],
+        Regression::ConstantTrendSquared => [
+            [-4.37113, -11.5882, -35.819, -334.047],
+            [-3.83239, -5.9057, -12.490, -118.284],
+            [-3.55326, -3.6596, -5.293, -63.559],
+        ],
+    }
+}
+
+/// MacKinnon (2010) response surfaces for N = 2.
+fn pair_tau_2010(regression: Regression) -> Option<[[f64; 4]; 3]> {
+    match regression {
+        Regression::NoConstant => None,
+        Regression::Constant => Some([
+            [-3.89644, -10.9519, -33.527, 0.0],
+            [-3.33613, -6.1101, -6.823, 0.0],
+            [-3.04445, -4.2412, -2.720, 0.0],
+        ]),
+        Regression::ConstantTrend => Some([
+            [-4.32762, -15.4387, -35.679, 0.0],
+            [-3.78057, -9.5106, -12.074, 0.0],
+            [-3.49631, -7.0815, -7.538, 21.892],
+        ]),
+        Regression::ConstantTrendSquared => Some([
+            [-4.69276, -20.2284, -64.919, 88.884],
+            [-4.15387, -13.3114, -28.402, 72.741],
+            [-3.87346, -10.4637, -17.408, 66.313],
+        ]),
+    }
+}
+
+/// Tables for `n_variables` series, or an error naming what is tabulated.
+fn cointegration_tables(regression: Regression, n_variables: usize) -> Result<(TauCoefficients, [[f64; 4]; 3]), Error> {
+    let tables = match n_variables {
+        1 => Some((tau_coefficients(regression), tau_2010(regression))),
+        2 => pair_tau_coefficients(regression).zip(pair_tau_2010(regression)),
+        _ => None,
+    };
+    tables.ok_or_else(|| {
+        Error::InvalidOption(format!(
+            "no MacKinnon tables for {} variables with regression \"{}\" (N = 1, or N = 2 with \"c\", \"ct\" or \"ctt\")",
+            n_variables, regression
+        ))
+    })
+}
+
+/// Asymptotic 1%, 5% and 10% critical values (the leading response-surface terms).
+pub fn asymptotic_critical_values(regression: Regression) -> CriticalValues {
+    surface_critical_values(tau_2010(regression), 0.0)
+}
+
+/// 1%, 5% and 10% critical values for a regression estimated on `nobs` observations.
+pub fn critical_values(regression: Regression, nobs: usize) -> CriticalValues {
+    surface_critical_values(tau_2010(regression), 1.0 / nobs.max(1) as f64)
+}
+
+/// Critical values of the residual-based cointegration test on `n_variables` series whose
+/// residual regression has `nobs` observations.
+pub fn cointegration_critical_values(regression: Regression, n_variables: usize, nobs: usize) -> Result<CriticalValues, Error> {
+    let (_, surface) = cointegration_tables(regression, n_variables)?;
+    Ok(surface_critical_values(surface, 1.0 / nobs.max(1) as f64))
+}
+
+/// Asymptotic p-value of the residual-based cointegration test on `n_variables` series,
+/// statsmodels' `mackinnonp(N=n_variables)`.
+pub fn cointegration_p_value(test_statistic: f64, regression: Regression, n_variables: usize) -> Result<f64, Error> {
+    let (coefficients, _) = cointegration_tables(regression, n_variables)?;
+    Ok(tau_p_value(test_statistic, &coefficients))
+}
+
+fn surface_critical_values([one, five, ten]: [[f64; 4]; 3], inv_t: f64) -> CriticalValues {
+    let surface = |b: [f64; 4]| b[0] + inv_t * (b[1] + inv_t * (b[2] + inv_t * b[3]));
+    CriticalValues {
+        one_percent: surface(one),
+        five_percent: surface(five),
+        ten_percent: surface(ten),
+    }
+}
+
+/// Evaluates `sum(coefficients[i] * scaling[i] * x^i)`.
+fn polyval(coefficients: &[f64], scaling: &[f64], x: f64) -> f64 {
+    coefficients
+        .iter()
+        .zip(scaling)
+        .rev()
+        .fold(0.0, |acc, (c, s)| acc * x + c * s)
+}
@@ -0,0 +1,157 @@
+//! Ordinary least squares through a Householder QR decomposition.
+//!
+//! Solving `R b = Q'y` never forms `X'X`, whose condition number is the square of `X`'s.
+//! That matters for price levels in the thousands next to a constant column. Columns are
+//! scaled to unit norm before factorising so that rank detection does not depend on the
+//! units of the regressors.
+
+use nalgebra::linalg::QR;
+use nalgebra::{DMatrix, DVector, Dyn};
+
+use crate::error::Error;
+
+/// A fitted least-squares regression.
+#[derive(Clone, Debug)]
+pub struct OlsFit {
+    pub params: DVector<f64>,
+    pub residuals: DVector<f64>,
+    pub ssr: f64,
+    pub nobs: usize,
+    /// Numerical rank of the design matrix (always equal to the number of columns for a
+    /// successful fit)
+    pub rank: usize,
+    /// Ratio of the largest to the smallest singular value of the design matrix
+    pub condition_number: f64,
+    /// `R^-1` in the original (unscaled) units, so that `(X'X)^-1 = R^-1 R^-T`
+    r_inverse: DMatrix<f64>,
+}
+
+impl OlsFit {
+    pub fn n_params(&self) -> usize {
+        self.params.len()
+    }
+
+    pub fn df_resid(&self) -> usize {
+        self.nobs - self.n_params()
+    }
+
+    /// Residual variance `ssr / (nobs - k)`.
+    pub fn sigma2(&self) -> f64 {
+        self.ssr / self.df_resid() as f64
+    }
+
+    /// `(X'X)^-1`, computed from the triangular factor.
+    pub fn normalized_cov_params(&self) -> DMatrix<f64> {
+        &self.r_inverse * self.r_inverse.transpose()
+    }
+
+    /// Coefficient covariance `sigma2 * (X'X)^-1`.
+    pub fn cov_params(&self) -> DMatrix<f64> {
+        self.normalized_cov_params() * self.sigma2()
+    }
+
+    pub fn std_errors(&self) -> DVector<f64> {
+        let sigma = self.sigma2().sqrt();
+        DVector::from_iterator(
+            self.n_params(),
+            self.r_inverse.row_iter().map(|row| sigma * row.norm()),
+        )
+    }
+
+    pub fn t_values(&self) -> DVector<f64> {
+        self.params.component_div(&self.std_errors())
+    }
+
+    /// R-squared, centred when the regression has a constant and uncentred otherwise.
+    pub fn r_squared(&self, y: &DVector<f64>, has_constant: bool) -> f64 {
+        let mean = if has_constant { y.mean() } else { 0.0 };
+        let tss: f64 = y.iter().map(|v| (v - mean).powi(2)).sum();
+        1.0 - self.ssr / tss
+    }
+}
+
+/// Fits `y = X b + e`. Fails with [`Error::SingularDesign`] when `X` is rank deficient and
+/// with [`Error::InsufficientData`] when there are no residual degrees of freedom.
+pub fn ols(x: &DMatrix<f64>, y: &DVector<f64>) -> Result<OlsFit, Error> {
+    let (nobs, k) = x.shape();
+    if nobs <= k {
+        return Err(Error::InsufficientData {
+            required: k + 1,
+            actual: nobs,
+        });
+    }
+    if y.iter().any(|v| !v.is_finite()) {
+        return Err(Error::SingularDesign);
+    }
+    let (qr, scales) = scaled_qr(x)?;
+    let r = qr.r();
+
+    let mut qty = y.clone();
+    qr.q_tr_mul(&mut qty);
+    let scaled_params = r
+        .solve_upper_triangular(&qty.rows(0, k).into_owned())
+        .ok_or(Error::SingularDesign)?;
+    let scaled_r_inverse = r
+        .solve_upper_triangular(&DMatrix::identity(k, k))
+        .ok_or(Error::SingularDesign)?;
+
+    // Undo the column scaling: b = D^-1 b_scaled and R^-1 = D^-1 R_scaled^-1
+    let mut params = scaled_params;
+    let mut r_inverse = scaled_r_inverse;
+    for (j, &scale) in scales.iter().enumerate() {
+        params[j] /= scale;
+        r_inverse.row_mut(j).scale_mut(1.0 / scale);
+    }
+
+    let residuals = y - x * &params;
+    let ssr = residuals.norm_squared();
+
+    // X = Q R_scaled D, so X shares its singular values with the small k x k R_scaled D
+    let mut r_unscaled = r;
+    for (mut column, &scale) in r_unscaled.column_iter_mut().zip(&scales) {
+        column *= scale;
+    }
+    let singular_values = r_unscaled.singular_values();
+    let condition_number = singular_values.max() / singular_values.min();
+
+    Ok(OlsFit {
+        params,
+        residuals,
+        ssr,
+        nobs,
+        rank: k,
+        condition_number,
+        r_inverse,
+    })
+}
+
+type DynQr = QR<f64, Dyn, Dyn>;
+
+/// Householder QR of `x` with its columns scaled to unit norm, and the scales, so that
+/// `x = Q R diag(scales)`. Fails with [`Error::SingularDesign`] when `x` is not finite or
+/// is rank deficient at a tolerance that does not depend on the units of its columns.
+pub(crate) fn scaled_qr(x: &DMatrix<f64>) -> Result<(DynQr, Vec<f64>), Error> {
+    let (nobs, k) = x.shape();
+    if x.iter().any(|v| !v.is_finite()) {
+        return Err(Error::SingularDesign);
+    }
+
+    // Equilibrate the columns; an all-zero column is rank deficient by definition
+    let scales: Vec<f64> = x.column_iter().map(|column| column.norm()).collect();
+    if scales.contains(&0.0) {
+        return Err(Error::SingularDesign);
+    }
+    let mut scaled = x.clone();
+    for (mut column, &scale) in scaled.column_iter_mut().zip(&scales) {
+        column /= scale;
+    }
+
+    let qr = scaled.qr();
+    let scaled_singular_values = qr.r().singular_values();
+    let tolerance = nobs.max(k) as f64 * f64::EPSILON * scaled_singular_values.max();
+    let rank = scaled_singular_values.iter().filter(|&&s| s > tolerance).count();
+    if rank < k {
+        return Err(Error::SingularDesign);
+    }
+    Ok((qr, scales))
+}
@@ -0,0 +1,360 @@
+//! Ornstein-Uhlenbeck fit of a spread, `dX = theta * (mu - X) dt + sigma dW`, replacing the
+//! worker's `calculateHalfLife`.
+//!
+//! Sampled every `dt`, an OU process is exactly the AR(1) `X_t = mu + phi * (X_{t-1} - mu) +
+//! e_t` with `phi = exp(-theta * dt)` and `Var(e) = sigma^2 * (1 - phi^2) / (2 * theta)`.
+//! Two estimators are offered:
+//!
+//! * "ar1": OLS of `X_t` on `X_{t-1}` mapped to the OU parameters, with standard errors from
+//!   the regression and the delta method. This is the worker's regression.
+//! * "mle": exact maximum likelihood, which also uses the first observation's stationary
+//!   distribution, with standard errors from the asymptotic information matrix.
+//!
+//! Intervals are 95%. Those for theta and the half-life map the interval for `phi`, so a
+//! `phi` interval reaching 1 gives an infinite upper half-life: the data cannot rule out a
+//! random walk.
+//!
+//! A fitted `phi` outside (0, 1) has no OU counterpart. With `phi <= 0` the spread
+//! overshoots the mean every bar; it is still a stationary AR(1), so `mu` and
+//! `equilibrium_std` are reported, while `theta`, `sigma` and `half_life` are NaN in all four
+//! fields. With `phi >= 1` the spread is a random walk or explosive and only `phi` is
+//! reported; every field of the other parameters is NaN.
+
+use std::f64::consts::LN_2;
+use std::str::FromStr;
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{self, Error};
+use crate::hedge::WindowMoments;
+
+/// Fewest observations accepted, as in the worker.
+const MIN_OBSERVATIONS: usize = 20;
+
+/// Two-sided 95% normal quantile.
+const Z_95: f64 = 1.959_963_984_540_054;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OuMethod {
+    /// "ar1": OLS of the AR(1) mapped to the OU parameters
+    Ar1,
+    /// "mle": exact maximum likelihood including the first observation
+    Mle,
+}
+
+impl FromStr for OuMethod {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ar1" | "ols" => Ok(OuMethod::Ar1),
+            "mle" | "exact" => Ok(OuMethod::Mle),
+            other => Err(Error::InvalidOption(format!(
+                "unknown OU method \"{}\" (expected \"ar1\" or \"mle\")",
+                other
+            ))),
+        }
+    }
+}
+
+/// An estimate with its standard error and 95% interval.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OuParameter {
+    pub estimate: f64,
+    pub std_error: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl OuParameter {
+    /// A parameter the fit does not define; see the module doc
+    const UNDEFINED: OuParameter = OuParameter {
+        estimate: f64::NAN,
+        std_error: f64::NAN,
+        lower: f64::NAN,
+        upper: f64::NAN,
+    };
+
+    fn wald(estimate: f64, std_error: f64) -> Self {
+        OuParameter {
+            estimate,
+            std_error,
+            lower: estimate - Z_95 * std_error,
+            upper: estimate + Z_95 * std_error,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct OuEstimate {
+    theta: OuParameter,
+    mu: OuParameter,
+    sigma: OuParameter,
+    half_life: OuParameter,
+    equilibrium_std: OuParameter,
+    phi: OuParameter,
+    /// Gaussian log-likelihood at the estimate
+    pub log_likelihood: f64,
+    pub nobs: usize,
+    /// Sampling interval the rates are expressed in
+    pub dt: f64,
+    /// `phi` is in (0, 1) and its interval stays below 1
+    pub is_mean_reverting: bool,
+}
+
+#[wasm_bindgen]
+impl OuEstimate {
+    /// Speed of mean reversion per unit of `dt`
+    #[wasm_bindgen(getter)]
+    pub fn theta(&self) -> OuParameter {
+        self.theta
+    }
+
+    /// Long-run mean
+    #[wasm_bindgen(getter)]
+    pub fn mu(&self) -> OuParameter {
+        self.mu
+    }
+
+    /// Diffusion per square root of `dt`
+    #[wasm_bindgen(getter)]
+    pub fn sigma(&self) -> OuParameter {
+        self.sigma
+    }
+
+    /// `ln 2 / theta`, in units of `dt`
+    #[wasm_bindgen(getter)]
+    pub fn half_life(&self) -> OuParameter {
+        self.half_life
+    }
+
+    /// Stationary standard deviation `sigma / sqrt(2 * theta)`
+    #[wasm_bindgen(getter)]
+    pub fn equilibrium_std(&self) -> OuParameter {
+        self.equilibrium_std
+    }
+
+    /// AR(1) coefficient `exp(-theta * dt)`
+    #[wasm_bindgen(getter)]
+    pub fn phi(&self) -> OuParameter {
+        self.phi
+    }
+}
+
+/// Fits an OU process to `data` with method "ar1" or "mle"; `dt` defaults to one bar.
+#[wasm_bindgen]
+pub fn calculate_ou_estimate(data: Vec<f64>, method: &str, dt: Option<f64>) -> Result<OuEstimate, JsError> {
+    Ok(ou_estimate(&data, method.parse()?, dt.unwrap_or(1.0))?)
+}
+
+pub fn ou_estimate(data: &[f64], method: OuMethod, dt: f64) -> Result<OuEstimate, Error> {
+    if !(dt > 0.0 && dt.is_finite()) {
+        return Err(Error::InvalidOption(format!("dt must be positive, got {}", dt)));
+    }
+    error::validate_series(data, MIN_OBSERVATIONS)?;
+    match method {
+        OuMethod::Ar1 => ar1_estimate(data, dt),
+        OuMethod::Mle => exact_mle(data, dt),
+    }
+}
+
+/// OLS of `X_t` on `X_{t-1}`.
+fn ar1_estimate(data: &[f64], dt: f64) -> Result<OuEstimate, Error> {
+    let m = WindowMoments::from_window(&data[1..], &data[..data.len() - 1]).centred();
+    if m.s_bb == 0.0 {
+        return Err(Error::SingularDesign);
+    }
+    let n = (data.len() - 1) as f64;
+    let phi = m.s_ab / m.s_bb;
+    let intercept = m.mean_a - phi * m.mean_b;
+    let ssr = (m.s_aa - phi * m.s_ab).max(0.0);
+    let sigma_e2 = ssr / (n - 2.0);
+    let sigma_e = sigma_e2.sqrt();
+
+    let var_phi = sigma_e2 / m.s_bb;
+    let var_intercept = sigma_e2 * (1.0 / n + m.mean_b * m.mean_b / m.s_bb);
+    let cov = -sigma_e2 * m.mean_b / m.s_bb;
+    let var_sigma_e = sigma_e2 / (2.0 * (n - 2.0));
+
+    let mu = intercept / (1.0 - phi);
+    // Gradient of mu = c / (1 - phi) in (c, phi)
+    let (d_c, d_phi) = (1.0 / (1.0 - phi), intercept / (1.0 - phi).powi(2));
+    let var_mu = d_c * d_c * var_intercept + 2.0 * d_c * d_phi * cov + d_phi * d_phi * var_phi;
+
+    Ok(Ar1Fit {
+        phi,
+        var_phi,
+        mu,
+        var_mu: var_mu.max(0.0),
+        sigma_e,
+        var_sigma_e,
+        log_likelihood: -0.5 * n * ((2.0 * std::f64::consts::PI * ssr / n).ln() + 1.0),
+    }
+    .to_ou(dt, data.len()))
+}
+
+/// Exact AR(1) likelihood with `X_0` drawn from the stationary distribution. For a given
+/// `phi` the best `mu` and innovation variance have closed forms, so only `phi` is searched.
+fn exact_mle(data: &[f64], dt: f64) -> Result<OuEstimate, Error> {
+    // Demeaning first keeps the sums of squares well conditioned for price-level spreads
+    let centre = data.iter().sum::<f64>() / data.len() as f64;
+    let x: Vec<f64> = data.iter().map(|v| v - centre).collect();
+    let n = x.len() as f64;
+
+    // Search phi = tanh(z): a coarse grid, then golden section around the best point
+    const GRID: usize = 400;
+    const Z_MAX: f64 = 8.0;
+    let step = 2.0 * Z_MAX / GRID as f64;
+    let best = (0..=GRID)
+        .map(|i| -Z_MAX + i as f64 * step)
+        .map(|z| (z, profile(&x, z.tanh()).0))
+        .filter(|(_, ll)| ll.is_finite())
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .ok_or(Error::SingularDesign)?;
+    let (mut lo, mut hi) = (best.0 - step, best.0 + step);
+    let ratio = (5.0_f64.sqrt() - 1.0) / 2.0;
+    let mut c = hi - ratio * (hi - lo);
+    let mut d = lo + ratio * (hi - lo);
+    let (mut fc, mut fd) = (profile(&x, c.tanh()).0, profile(&x, d.tanh()).0);
+    while hi - lo > 1e-12 {
+        if fc > fd {
+            hi = d;
+            d = c;
+            fd = fc;
+            c = hi - ratio * (hi - lo);
+            fc = profile(&x, c.tanh()).0;
+        } else {
+            lo = c;
+            c = d;
+            fc = fd;
+            d = lo + ratio * (hi - lo);
+            fd = profile(&x, d.tanh()).0;
+        }
+    }
+    let phi = (0.5 * (lo + hi)).tanh();
+    let (log_likelihood, mu, sigma_e2) = profile(&x, phi);
+    if !log_likelihood.is_finite() {
+        return Err(Error::SingularDesign);
+    }
+
+    // Variances from the inverse information of the stationary Gaussian AR(1), under which
+    // the three estimates are asymptotically uncorrelated
+    Ok(Ar1Fit {
+        phi,
+        var_phi: (1.0 - phi * phi) / n,
+        mu: mu + centre,
+        var_mu: sigma_e2 / (n * (1.0 - phi).powi(2)),
+        sigma_e: sigma_e2.sqrt(),
+        var_sigma_e: sigma_e2 / (2.0 * n),
+        log_likelihood,
+    }
+    .to_ou(dt, data.len()))
+}
+
+/// `(log-likelihood, mu, innovation variance)` maximised over `mu` and the variance for
+/// a fixed `phi`.
+fn profile(x: &[f64], phi: f64) -> (f64, f64, f64) {
+    let n = x.len() as f64;
+    let stationary = 1.0 - phi * phi;
+    // mu minimises stationary * (x_0 - mu)^2 + sum_t (x_t - phi x_{t-1} - (1 - phi) mu)^2
+    let quasi_sum: f64 = x.windows(2).map(|w| w[1] - phi * w[0]).sum();
+    let mu = (stationary * x[0] + (1.0 - phi) * quasi_sum) / (stationary + (n - 1.0) * (1.0 - phi).powi(2));
+    let first = stationary * (x[0] - mu).powi(2);
+    let rest: f64 = x
+        .windows(2)
+        .map(|w| (w[1] - mu - phi * (w[0] - mu)).powi(2))
+        .sum();
+    let sigma_e2 = (first + rest) / n;
+    let log_likelihood = -0.5 * n * ((2.0 * std::f64::consts::PI * sigma_e2).ln() + 1.0) + 0.5 * stationary.ln();
+    (log_likelihood, mu, sigma_e2)
+}
+
+/// AR(1) estimates with the variances of `phi`, `mu` and the innovation standard deviation.
+struct Ar1Fit {
+    phi: f64,
+    var_phi: f64,
+    mu: f64,
+    var_mu: f64,
+    sigma_e: f64,
+    var_sigma_e: f64,
+    log_likelihood: f64,
+}
+
+impl Ar1Fit {
+    /// Maps the AR(1) estimates to the OU parameters.
+    fn to_ou(&self, dt: f64, nobs: usize) -> OuEstimate {
+        let Ar1Fit {
+            phi,
+            var_phi,
+            mu,
+            var_mu,
+            sigma_e,
+            var_sigma_e,
+            log_likelihood,
+        } = *self;
+        let phi_parameter = OuParameter::wald(phi, var_phi.sqrt());
+        let stationary = 1.0 - phi * phi;
+        let mut estimate = OuEstimate {
+            theta: OuParameter::UNDEFINED,
+            mu: OuParameter::UNDEFINED,
+            sigma: OuParameter::UNDEFINED,
+            half_life: OuParameter::UNDEFINED,
+            equilibrium_std: OuParameter::UNDEFINED,
+            phi: phi_parameter,
+            log_likelihood,
+            nobs,
+            dt,
+            is_mean_reverting: phi > 0.0 && phi_parameter.upper < 1.0,
+        };
+        if phi.is_nan() || phi >= 1.0 {
+            return estimate;
+        }
+        estimate.mu = OuParameter::wald(mu, var_mu.sqrt());
+        let equilibrium_std = sigma_e / stationary.sqrt();
+        let se_equilibrium_std =
+            ((sigma_e * phi / stationary.powf(1.5)).powi(2) * var_phi + var_sigma_e / stationary).sqrt();
+        estimate.equilibrium_std = OuParameter::wald(equilibrium_std, se_equilibrium_std);
+        if phi <= 0.0 {
+            return estimate;
+        }
+
+        // Delta method for the standard errors
+        let theta = -phi.ln() / dt;
+        let se_theta = var_phi.sqrt() / (phi * dt);
+        let half_life = LN_2 / theta;
+        let se_half_life = LN_2 * se_theta / (theta * theta);
+        let sigma = sigma_e * (2.0 * theta / stationary).sqrt();
+        let d_log_sigma = -1.0 / (2.0 * theta * phi * dt) + phi / stationary;
+        let se_sigma = sigma * (d_log_sigma * d_log_sigma * var_phi + var_sigma_e / (sigma_e * sigma_e)).sqrt();
+
+        // theta and the half-life are monotone in phi, so their intervals come from phi's
+        let theta_at = |p: f64| {
+            if p >= 1.0 {
+                0.0
+            } else if p <= 0.0 {
+                f64::INFINITY
+            } else {
+                -p.ln() / dt
+            }
+        };
+        let theta_lower = theta_at(phi_parameter.upper);
+        let theta_upper = theta_at(phi_parameter.lower);
+
+        estimate.theta = OuParameter {
+            estimate: theta,
+            std_error: se_theta,
+            lower: theta_lower,
+            upper: theta_upper,
+        };
+        estimate.sigma = OuParameter::wald(sigma, se_sigma);
+        estimate.half_life = OuParameter {
+            estimate: half_life,
+            std_error: se_half_life,
+            lower: LN_2 / theta_upper,
+            upper: LN_2 / theta_lower,
+        };
+        estimate
+    }
+}
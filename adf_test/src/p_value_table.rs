@@ -0,0 +1,47 @@
+//! Dickey-Fuller p-values for the constant-only regression, interpolated from a table of
+//! the statistic's distribution. `build.rs` reduces the original 50,000-row table in
+//! `data/adf_p_value_lookup.csv` to the knots needed to stay within [`P_VALUE_TOLERANCE`]
+//! of it.
+
+include!(concat!(env!("OUT_DIR"), "/adf_p_value_knots.rs"));
+
+/// Number of knots kept from the original table.
+pub fn knot_count() -> usize {
+    ADF_P_VALUE_KNOTS.len()
+}
+
+/// P-value of `test_statistic` by linear interpolation between the knots, clamped to the
+/// first and last knot outside the table.
+pub fn interpolate_p_value(test_statistic: f64) -> f64 {
+    if test_statistic <= ADF_P_VALUE_KNOTS[0][0] {
+        return ADF_P_VALUE_KNOTS[0][1];
+    }
+    if test_statistic >= ADF_P_VALUE_KNOTS[ADF_P_VALUE_KNOTS.len() - 1][0] {
+        return ADF_P_VALUE_KNOTS[ADF_P_VALUE_KNOTS.len() - 1][1];
+    }
+
+    let mut low = 0;
+    let mut high = ADF_P_VALUE_KNOTS.len() - 1;
+    let mut idx = 0;
+
+    // Find the interval using binary search
+    while low <= high {
+        let mid = low + (high - low) / 2;
+        if ADF_P_VALUE_KNOTS[mid][0] == test_statistic {
+            return ADF_P_VALUE_KNOTS[mid][1];
+        } else if ADF_P_VALUE_KNOTS[mid][0] < test_statistic {
+            idx = mid;
+            low = mid + 1;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    let x1 = ADF_P_VALUE_KNOTS[idx][0];
+    let y1 = ADF_P_VALUE_KNOTS[idx][1];
+    let x2 = ADF_P_VALUE_KNOTS[idx + 1][0];
+    let y2 = ADF_P_VALUE_KNOTS[idx + 1][1];
+
+    // Linear interpolation formula
+    y1 + (test_statistic - x1) * (y2 - y1) / (x2 - x1)
+}
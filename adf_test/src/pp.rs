@@ -0,0 +1,180 @@
+//! Phillips-Perron (1988) unit root test. The Dickey-Fuller regression is run without
+//! lagged differences and the statistics are corrected non-parametrically for serial
+//! correlation and heteroskedasticity through a Newey-West long-run variance, which avoids
+//! ADF's lag selection on noisy spreads.
+
+use nalgebra::{DMatrix, DVector};
+use wasm_bindgen::prelude::*;
+
+use crate::error::{self, Error};
+use crate::long_run_variance::{self, Bandwidth};
+use crate::mackinnon;
+use crate::ols;
+use crate::regression::Regression;
+use crate::CriticalValues;
+
+/// Sample sizes of Fuller's (1976) table for the normalised bias statistic n(rho - 1);
+/// `None` is the asymptotic row.
+const Z_ALPHA_SAMPLE_SIZES: [Option<usize>; 6] = [Some(25), Some(50), Some(100), Some(250), Some(500), None];
+
+/// Fuller's 1%, 5% and 10% critical values for each sample size above.
+fn z_alpha_table(regression: Regression) -> [[f64; 3]; 6] {
+    match regression {
+        Regression::NoConstant => [
+            [-11.9, -7.3, -5.3],
+            [-12.9, -7.7, -5.5],
+            [-13.3, -7.9, -5.6],
+            [-13.6, -8.0, -5.7],
+            [-13.7, -8.0, -5.7],
+            [-13.8, -8.1, -5.7],
+        ],
+        Regression::Constant => [
+            [-17.2, -12.5, -10.2],
+            [-18.9, -13.3, -10.7],
+            [-19.8, -13.7, -11.0],
+            [-20.3, -14.0, -11.2],
+            [-20.5, -14.0, -11.2],
+            [-20.7, -14.1, -11.3],
+        ],
+        _ => [
+            [-22.5, -17.9, -15.6],
+            [-25.7, -19.8, -16.8],
+            [-27.4, -20.7, -17.5],
+            [-28.4, -21.3, -18.0],
+            [-28.9, -21.5, -18.1],
+            [-29.5, -21.8, -18.3],
+        ],
+    }
+}
+
+#[wasm_bindgen]
+pub struct PpResult {
+    /// Z(t), compared with the Dickey-Fuller tau distribution
+    pub test_statistic: f64,
+    /// Z(alpha), the corrected normalised bias n(rho - 1)
+    pub z_alpha: f64,
+    /// Newey-West bandwidth used for the long-run variance
+    pub lags: u32,
+    pub nobs: u32,
+    /// MacKinnon p-value of Z(t)
+    pub p_value: f64,
+    critical_values: CriticalValues,
+    z_alpha_critical_values: CriticalValues,
+    pub is_stationary: bool,
+}
+
+#[wasm_bindgen]
+impl PpResult {
+    /// Critical values of Z(t)
+    #[wasm_bindgen(getter)]
+    pub fn critical_values(&self) -> JsValue {
+        self.critical_values.to_js()
+    }
+
+    /// Critical values of Z(alpha), interpolated from Fuller's table
+    #[wasm_bindgen(getter)]
+    pub fn z_alpha_critical_values(&self) -> JsValue {
+        self.z_alpha_critical_values.to_js()
+    }
+}
+
+impl PpResult {
+    pub fn critical_value_table(&self) -> CriticalValues {
+        self.critical_values
+    }
+
+    pub fn z_alpha_critical_value_table(&self) -> CriticalValues {
+        self.z_alpha_critical_values
+    }
+}
+
+/// Phillips-Perron test with deterministic terms "n", "c" or "ct". `lags` fixes the
+/// Newey-West bandwidth; leave it `undefined` for `ceil(12 * (n / 100)^(1/4))`.
+#[wasm_bindgen]
+pub fn calculate_pp_test(data: Vec<f64>, regression: &str, lags: Option<u32>) -> Result<PpResult, JsError> {
+    let bandwidth = lags.map_or(Bandwidth::Schwert, |lags| Bandwidth::Fixed(lags as usize));
+    Ok(pp_test(&data, regression.parse()?, bandwidth)?)
+}
+
+pub fn pp_test(data: &[f64], regression: Regression, bandwidth: Bandwidth) -> Result<PpResult, Error> {
+    if regression == Regression::ConstantTrendSquared {
+        return Err(Error::InvalidOption(
+            "Phillips-Perron supports the \"n\", \"c\" and \"ct\" regressions".to_string(),
+        ));
+    }
+    error::validate_series(data, regression.n_terms() + 4)?;
+
+    // Delta y_t on the deterministic terms and y_{t-1}
+    let nobs = data.len() - 1;
+    let n_trend = regression.n_terms();
+    let deterministic = regression.design(nobs);
+    let x = DMatrix::from_fn(nobs, n_trend + 1, |i, k| {
+        if k < n_trend {
+            deterministic[(i, k)]
+        } else {
+            data[i]
+        }
+    });
+    let y = DVector::from_fn(nobs, |i, _| data[i + 1] - data[i]);
+    let fit = ols::ols(&x, &y)?;
+
+    let residuals = fit.residuals.as_slice();
+    let lags = match bandwidth {
+        // The Schwert rule is applied to the full series length, as in arch
+        Bandwidth::Schwert => long_run_variance::schwert_bandwidth(data.len()).min(nobs - 1),
+        other => other.lags(residuals),
+    };
+    let lambda2 = long_run_variance::newey_west(residuals, lags);
+    let n = nobs as f64;
+    let k = fit.n_params() as f64;
+    let s2 = fit.ssr / (n - k);
+    let gamma0 = fit.ssr / n;
+    let gamma = fit.params[n_trend];
+    let sigma = fit.std_errors()[n_trend];
+
+    let z_t = (gamma0 / lambda2).sqrt() * (gamma / sigma)
+        - 0.5 * ((lambda2 - gamma0) / lambda2.sqrt()) * (n * sigma / s2.sqrt());
+    let z_alpha = n * gamma - 0.5 * (n * n * sigma * sigma / s2) * (lambda2 - gamma0);
+    if !z_t.is_finite() || !z_alpha.is_finite() {
+        return Err(Error::SingularDesign);
+    }
+
+    let p_value = mackinnon::mackinnonp(z_t, regression);
+    let critical_values = mackinnon::critical_values(regression, nobs);
+
+    Ok(PpResult {
+        test_statistic: z_t,
+        z_alpha,
+        lags: lags as u32,
+        nobs: nobs as u32,
+        p_value,
+        critical_values,
+        z_alpha_critical_values: z_alpha_critical_values(regression, nobs),
+        is_stationary: crate::determine_stationarity(z_t, &critical_values),
+    })
+}
+
+/// Fuller's table interpolated linearly in 1/n, with the asymptotic row at 1/n = 0.
+fn z_alpha_critical_values(regression: Regression, nobs: usize) -> CriticalValues {
+    let table = z_alpha_table(regression);
+    let inv = |size: Option<usize>| size.map_or(0.0, |s| 1.0 / s as f64);
+    let target = 1.0 / nobs.max(1) as f64;
+
+    let row = if target >= inv(Z_ALPHA_SAMPLE_SIZES[0]) {
+        table[0]
+    } else {
+        let k = Z_ALPHA_SAMPLE_SIZES
+            .windows(2)
+            .position(|w| target <= inv(w[0]) && target >= inv(w[1]))
+            .unwrap_or(table.len() - 2);
+        let (x1, x2) = (inv(Z_ALPHA_SAMPLE_SIZES[k]), inv(Z_ALPHA_SAMPLE_SIZES[k + 1]));
+        let w = (target - x2) / (x1 - x2);
+        [0, 1, 2].map(|j| w * table[k][j] + (1.0 - w) * table[k + 1][j])
+    };
+
+    CriticalValues {
+        one_percent: row[0],
+        five_percent: row[1],
+        ten_percent: row[2],
+    }
+}
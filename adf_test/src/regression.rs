@@ -0,0 +1,82 @@
+use std::fmt;
+use std::str::FromStr;
+
+use nalgebra::DMatrix;
+
+use crate::error::Error;
+
+/// Deterministic terms included in a unit root test regression. The string
+/// codes match statsmodels' `adfuller(regression=...)`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Regression {
+    /// "n" (or "nc"): no deterministic terms
+    NoConstant,
+    /// "c": constant only
+    #[default]
+    Constant,
+    /// "ct": constant and linear trend
+    ConstantTrend,
+    /// "ctt": constant, linear and quadratic trend
+    ConstantTrendSquared,
+}
+
+impl Regression {
+    /// Number of deterministic columns this specification adds to the design matrix.
+    pub fn n_terms(self) -> usize {
+        match self {
+            Regression::NoConstant => 0,
+            Regression::Constant => 1,
+            Regression::ConstantTrend => 2,
+            Regression::ConstantTrendSquared => 3,
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Regression::NoConstant => "n",
+            Regression::Constant => "c",
+            Regression::ConstantTrend => "ct",
+            Regression::ConstantTrendSquared => "ctt",
+        }
+    }
+
+    /// Value of the `column`-th deterministic regressor at (1-based) time index `t`.
+    pub(crate) fn term(self, column: usize, t: f64) -> f64 {
+        debug_assert!(column < self.n_terms());
+        match column {
+            0 => 1.0,
+            1 => t,
+            _ => t * t,
+        }
+    }
+}
+
+impl Regression {
+    /// The deterministic regressors alone for `nobs` observations, trend counted from 1.
+    pub(crate) fn design(self, nobs: usize) -> DMatrix<f64> {
+        DMatrix::from_fn(nobs, self.n_terms(), |i, k| self.term(k, (i + 1) as f64))
+    }
+}
+
+impl FromStr for Regression {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "n" | "nc" => Ok(Regression::NoConstant),
+            "c" => Ok(Regression::Constant),
+            "ct" => Ok(Regression::ConstantTrend),
+            "ctt" => Ok(Regression::ConstantTrendSquared),
+            other => Err(Error::InvalidOption(format!(
+                "unknown regression \"{}\" (expected one of \"n\", \"c\", \"ct\", \"ctt\")",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
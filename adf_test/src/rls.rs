@@ -0,0 +1,205 @@
+//! Recursive least squares hedge ratio with exponential forgetting: each bar's alpha and
+//! beta minimise `sum_j lambda^(t - j) * (a_j - alpha - beta * b_j)^2` over the bars so far.
+//!
+//! It sits between the rolling OLS, which weights the last `window` bars equally, and the
+//! Kalman filter; the effective memory is about `1 / (1 - lambda)` bars. The estimator is
+//! stateful so that a live page can feed it one bar at a time, and it keeps the series it
+//! has produced in the same [`HedgeSeries`] form as the other hedge models.
+
+use nalgebra::{Matrix2, Vector2};
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::hedge::HedgeSeries;
+
+/// Variance of the prior on alpha and beta when none is given.
+pub const DEFAULT_INITIAL_VARIANCE: f64 = 1000.0;
+
+/// Estimates and diagnostics of one bar.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct RlsStep {
+    pub alpha: f64,
+    pub beta: f64,
+    /// Gain applied to the prediction error for alpha
+    pub gain_alpha: f64,
+    /// Gain applied to the prediction error for beta
+    pub gain_beta: f64,
+    /// `a - (alpha + beta * b)` with the coefficients from before this bar
+    pub prediction_error: f64,
+    /// `a - (alpha + beta * b)` with the updated coefficients
+    pub spread: f64,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct RecursiveLeastSquares {
+    forgetting_factor: f64,
+    theta: Vector2<f64>,
+    p: Matrix2<f64>,
+    series: HedgeSeries,
+    gains_alpha: Vec<f64>,
+    gains_beta: Vec<f64>,
+    prediction_errors: Vec<f64>,
+}
+
+#[wasm_bindgen]
+impl RecursiveLeastSquares {
+    /// `forgetting_factor` in (0, 1]; `initial_variance` of the prior around alpha = 0 and
+    /// beta = 1 defaults to 1000.
+    #[wasm_bindgen(constructor)]
+    pub fn new(forgetting_factor: f64, initial_variance: Option<f64>) -> Result<RecursiveLeastSquares, JsError> {
+        Ok(RecursiveLeastSquares::with_forgetting_factor(forgetting_factor, initial_variance)?)
+    }
+
+    /// Folds in one bar and returns its estimates
+    pub fn update(&mut self, price_a: f64, price_b: f64) -> Result<RlsStep, JsError> {
+        Ok(self.step(price_a, price_b)?)
+    }
+
+    /// Folds in a batch of bars, in order
+    pub fn extend(&mut self, prices_a: Vec<f64>, prices_b: Vec<f64>) -> Result<(), JsError> {
+        Ok(self.process(&prices_a, &prices_b)?)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn alpha(&self) -> f64 {
+        self.theta[0]
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn beta(&self) -> f64 {
+        self.theta[1]
+    }
+
+    /// Bars processed so far
+    #[wasm_bindgen(getter)]
+    pub fn bars(&self) -> usize {
+        self.series.len()
+    }
+
+    /// Alpha, beta and spread of every bar processed so far
+    #[wasm_bindgen(getter)]
+    pub fn series(&self) -> HedgeSeries {
+        self.series.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn gains_alpha(&self) -> Vec<f64> {
+        self.gains_alpha.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn gains_beta(&self) -> Vec<f64> {
+        self.gains_beta.clone()
+    }
+
+    /// One-step-ahead prediction error of every bar processed so far
+    #[wasm_bindgen(getter)]
+    pub fn prediction_errors(&self) -> Vec<f64> {
+        self.prediction_errors.clone()
+    }
+}
+
+/// Runs a fresh RLS over the whole pair in one call; `update` carries on from the last bar.
+#[wasm_bindgen]
+pub fn calculate_rls_hedge(
+    prices_a: Vec<f64>,
+    prices_b: Vec<f64>,
+    forgetting_factor: f64,
+    initial_variance: Option<f64>,
+) -> Result<RecursiveLeastSquares, JsError> {
+    Ok(rls_hedge(&prices_a, &prices_b, forgetting_factor, initial_variance)?)
+}
+
+pub fn rls_hedge(
+    prices_a: &[f64],
+    prices_b: &[f64],
+    forgetting_factor: f64,
+    initial_variance: Option<f64>,
+) -> Result<RecursiveLeastSquares, Error> {
+    let mut rls = RecursiveLeastSquares::with_forgetting_factor(forgetting_factor, initial_variance)?;
+    rls.process(prices_a, prices_b)?;
+    Ok(rls)
+}
+
+impl RecursiveLeastSquares {
+    pub fn with_forgetting_factor(forgetting_factor: f64, initial_variance: Option<f64>) -> Result<Self, Error> {
+        if !(forgetting_factor > 0.0 && forgetting_factor <= 1.0) {
+            return Err(Error::InvalidOption(format!(
+                "forgetting_factor must be in (0, 1], got {}",
+                forgetting_factor
+            )));
+        }
+        let initial_variance = initial_variance.unwrap_or(DEFAULT_INITIAL_VARIANCE);
+        if !(initial_variance > 0.0 && initial_variance.is_finite()) {
+            return Err(Error::InvalidOption(format!(
+                "initial_variance must be positive, got {}",
+                initial_variance
+            )));
+        }
+        Ok(RecursiveLeastSquares {
+            forgetting_factor,
+            // The worker's fallback hedge until the data say otherwise
+            theta: Vector2::new(0.0, 1.0),
+            p: Matrix2::identity() * initial_variance,
+            series: HedgeSeries::default(),
+            gains_alpha: Vec::new(),
+            gains_beta: Vec::new(),
+            prediction_errors: Vec::new(),
+        })
+    }
+
+    pub fn forgetting_factor(&self) -> f64 {
+        self.forgetting_factor
+    }
+
+    pub fn hedge_series(&self) -> &HedgeSeries {
+        &self.series
+    }
+
+    pub fn step(&mut self, price_a: f64, price_b: f64) -> Result<RlsStep, Error> {
+        let index = self.bars();
+        if !price_a.is_finite() || !price_b.is_finite() {
+            return Err(Error::NonFiniteInput { index });
+        }
+
+        let h = Vector2::new(1.0, price_b);
+        let prediction_error = price_a - h.dot(&self.theta);
+        let p_h = self.p * h;
+        let gain = p_h / (self.forgetting_factor + h.dot(&p_h));
+        self.theta += gain * prediction_error;
+        self.p = (self.p - gain * p_h.transpose()) / self.forgetting_factor;
+        // Keep P symmetric; the update above lets rounding pull it apart over long runs
+        self.p = (self.p + self.p.transpose()) * 0.5;
+
+        let (alpha, beta) = (self.theta[0], self.theta[1]);
+        self.series.push(price_a, price_b, alpha, beta);
+        self.gains_alpha.push(gain[0]);
+        self.gains_beta.push(gain[1]);
+        self.prediction_errors.push(prediction_error);
+        Ok(RlsStep {
+            alpha,
+            beta,
+            gain_alpha: gain[0],
+            gain_beta: gain[1],
+            prediction_error,
+            spread: price_a - (alpha + beta * price_b),
+        })
+    }
+
+    /// Folds in a batch; on an error the bars before the offending one stay applied.
+    pub fn process(&mut self, prices_a: &[f64], prices_b: &[f64]) -> Result<(), Error> {
+        if prices_a.len() != prices_b.len() {
+            return Err(Error::InvalidOption(format!(
+                "both legs must have the same length ({} and {})",
+                prices_a.len(),
+                prices_b.len()
+            )));
+        }
+        for (&a, &b) in prices_a.iter().zip(prices_b) {
+            self.step(a, b)?;
+        }
+        Ok(())
+    }
+}
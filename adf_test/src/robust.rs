@@ -0,0 +1,408 @@
+//! Outlier-resistant hedge ratios for pairs whose prices contain earnings gaps or bad ticks,
+//! which drag an OLS beta towards themselves.
+//!
+//! Every estimator reports, for each bar, the Huber weight of its residual at the fitted
+//! line and whether the residual is an outlier, both measured against the robust scale
+//! `1.4826 * MAD` of the residuals. For the Huber estimator these are recomputed at the
+//! converged line rather than taken from its last IRLS iteration, which scored the residuals
+//! of the previous line; the two agree to within the convergence tolerance.
+//!
+//! Theil-Sen holds the slopes of all n(n-1)/2 pairs of bars, so its time and memory grow
+//! with the square of the sample: about 16 MB for 2000 bars. Samples and rolling windows
+//! longer than [`MAX_THEIL_SEN_BARS`] are rejected rather than left to exhaust the wasm
+//! heap. The rolling version reuses one buffer but still costs `O(window^2)` per bar.
+
+use std::str::FromStr;
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::hedge::{self, HedgeSeries};
+
+/// Huber tuning constant, 95% efficient when the noise is Gaussian.
+const HUBER_K: f64 = 1.345;
+
+/// Standardised residual beyond which a bar is flagged as an outlier.
+const OUTLIER_THRESHOLD: f64 = 2.5;
+
+/// Consistency factor turning a median absolute deviation into a Gaussian sigma.
+const MAD_SCALE: f64 = 1.4826;
+
+const MAX_ITERATIONS: usize = 200;
+
+/// Longest sample or window Theil-Sen accepts, about two million pairwise slopes.
+pub const MAX_THEIL_SEN_BARS: usize = 2000;
+
+/// Relative change in the coefficients below which IRLS stops.
+const TOLERANCE: f64 = 1e-10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RobustMethod {
+    /// "huber": Huber M-estimator fitted by iteratively reweighted least squares
+    Huber,
+    /// "theil-sen": median of the pairwise slopes, median residual as intercept
+    TheilSen,
+    /// "lad": least absolute deviations, fitted by iteratively reweighted least squares
+    Lad,
+}
+
+impl FromStr for RobustMethod {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "huber" => Ok(RobustMethod::Huber),
+            "theil-sen" | "theilsen" | "theil_sen" => Ok(RobustMethod::TheilSen),
+            "lad" | "l1" => Ok(RobustMethod::Lad),
+            other => Err(Error::InvalidOption(format!(
+                "unknown robust method \"{}\" (expected \"huber\", \"theil-sen\" or \"lad\")",
+                other
+            ))),
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub struct RobustHedgeResult {
+    pub alpha: f64,
+    /// Hedge ratio: units of B per unit of A
+    pub beta: f64,
+    /// Robust scale of the residuals, `1.4826 * MAD`
+    pub scale: f64,
+    /// IRLS iterations; zero for Theil-Sen
+    pub iterations: u32,
+    weights: Vec<f64>,
+    outliers: Vec<bool>,
+    series: HedgeSeries,
+}
+
+#[wasm_bindgen]
+impl RobustHedgeResult {
+    /// The fitted alpha and beta on every bar, with the spread they imply
+    #[wasm_bindgen(getter)]
+    pub fn series(&self) -> HedgeSeries {
+        self.series.clone()
+    }
+
+    /// Huber weight of each bar, 1 for bars inside the robust band
+    #[wasm_bindgen(getter)]
+    pub fn weights(&self) -> Vec<f64> {
+        self.weights.clone()
+    }
+
+    /// Array of booleans, one per bar
+    #[wasm_bindgen(getter)]
+    pub fn outliers(&self) -> JsValue {
+        bool_array(&self.outliers)
+    }
+}
+
+impl RobustHedgeResult {
+    pub fn hedge_series(&self) -> &HedgeSeries {
+        &self.series
+    }
+
+    pub fn weight_values(&self) -> &[f64] {
+        &self.weights
+    }
+
+    pub fn outlier_flags(&self) -> &[bool] {
+        &self.outliers
+    }
+}
+
+#[wasm_bindgen]
+pub struct RollingRobustHedgeResult {
+    series: HedgeSeries,
+    weights: Vec<f64>,
+    outliers: Vec<bool>,
+    pub window: usize,
+}
+
+#[wasm_bindgen]
+impl RollingRobustHedgeResult {
+    /// Alpha, beta and spread of each bar from the window ending at it
+    #[wasm_bindgen(getter)]
+    pub fn series(&self) -> HedgeSeries {
+        self.series.clone()
+    }
+
+    /// Huber weight of each bar within the window ending at it
+    #[wasm_bindgen(getter)]
+    pub fn weights(&self) -> Vec<f64> {
+        self.weights.clone()
+    }
+
+    /// Array of booleans: whether each bar is an outlier within the window ending at it
+    #[wasm_bindgen(getter)]
+    pub fn outliers(&self) -> JsValue {
+        bool_array(&self.outliers)
+    }
+}
+
+impl RollingRobustHedgeResult {
+    pub fn hedge_series(&self) -> &HedgeSeries {
+        &self.series
+    }
+
+    pub fn weight_values(&self) -> &[f64] {
+        &self.weights
+    }
+
+    pub fn outlier_flags(&self) -> &[bool] {
+        &self.outliers
+    }
+}
+
+/// Robust regression of `prices_a` on `prices_b` with method "huber", "theil-sen" or "lad".
+#[wasm_bindgen]
+pub fn calculate_robust_hedge(prices_a: Vec<f64>, prices_b: Vec<f64>, method: &str) -> Result<RobustHedgeResult, JsError> {
+    Ok(robust_hedge(&prices_a, &prices_b, method.parse()?)?)
+}
+
+/// Robust regression over a window of `window` bars ending at each bar, with the same
+/// warm-up and fallback as the rolling OLS.
+#[wasm_bindgen]
+pub fn calculate_rolling_robust_hedge(
+    prices_a: Vec<f64>,
+    prices_b: Vec<f64>,
+    window: usize,
+    method: &str,
+) -> Result<RollingRobustHedgeResult, JsError> {
+    Ok(rolling_robust_hedge(&prices_a, &prices_b, window, method.parse()?)?)
+}
+
+pub fn robust_hedge(prices_a: &[f64], prices_b: &[f64], method: RobustMethod) -> Result<RobustHedgeResult, Error> {
+    hedge::validate_pair(prices_a, prices_b, 3)?;
+    let fit = fit(prices_a, prices_b, method, &mut Vec::new())?;
+    let (weights, outliers) = classify(&fit.residuals, fit.scale);
+    let n = prices_a.len();
+    Ok(RobustHedgeResult {
+        alpha: fit.alpha,
+        beta: fit.beta,
+        scale: fit.scale,
+        iterations: fit.iterations as u32,
+        weights,
+        outliers,
+        series: HedgeSeries::new(prices_a, prices_b, vec![fit.alpha; n], vec![fit.beta; n]),
+    })
+}
+
+/// Refits the window ending at each bar, so the cost is that of one fit per bar, which for
+/// Theil-Sen is quadratic in `window`.
+pub fn rolling_robust_hedge(
+    prices_a: &[f64],
+    prices_b: &[f64],
+    window: usize,
+    method: RobustMethod,
+) -> Result<RollingRobustHedgeResult, Error> {
+    if window < 3 {
+        return Err(Error::InvalidOption(format!("window must be at least 3 bars, got {}", window)));
+    }
+    hedge::validate_pair(prices_a, prices_b, 1)?;
+    // A window fit that fails falls back silently, so refuse the oversized window up front
+    if method == RobustMethod::TheilSen {
+        check_theil_sen_size(window)?;
+    }
+
+    let n = prices_a.len();
+    let mut alphas = Vec::with_capacity(n);
+    let mut betas = Vec::with_capacity(n);
+    let mut weights = Vec::with_capacity(n);
+    let mut outliers = Vec::with_capacity(n);
+    let mut slopes = Vec::new();
+    for i in 0..n {
+        let start = (i + 1).saturating_sub(window);
+        match fit(&prices_a[start..=i], &prices_b[start..=i], method, &mut slopes) {
+            Ok(fit) => {
+                let residual = *fit.residuals.last().unwrap_or(&0.0);
+                let (weight, outlier) = classify_one(residual, fit.scale);
+                alphas.push(fit.alpha);
+                betas.push(fit.beta);
+                weights.push(weight);
+                outliers.push(outlier);
+            }
+            // Too few distinct B prices in the window: the rolling OLS fallback
+            Err(_) => {
+                alphas.push(0.0);
+                betas.push(1.0);
+                weights.push(1.0);
+                outliers.push(false);
+            }
+        }
+    }
+
+    Ok(RollingRobustHedgeResult {
+        series: HedgeSeries::new(prices_a, prices_b, alphas, betas),
+        weights,
+        outliers,
+        window,
+    })
+}
+
+struct Fit {
+    alpha: f64,
+    beta: f64,
+    residuals: Vec<f64>,
+    scale: f64,
+    iterations: usize,
+}
+
+/// `slopes` is scratch space for Theil-Sen, kept by the caller so rolling fits can reuse it.
+fn fit(prices_a: &[f64], prices_b: &[f64], method: RobustMethod, slopes: &mut Vec<f64>) -> Result<Fit, Error> {
+    let (alpha, beta, iterations) = match method {
+        RobustMethod::Huber => irls(prices_a, prices_b, huber_weight)?,
+        RobustMethod::Lad => irls(prices_a, prices_b, lad_weight)?,
+        RobustMethod::TheilSen => {
+            let (alpha, beta) = theil_sen(prices_a, prices_b, slopes)?;
+            (alpha, beta, 0)
+        }
+    };
+    let residuals = residuals(prices_a, prices_b, alpha, beta);
+    let scale = mad_scale(&residuals);
+    Ok(Fit {
+        alpha,
+        beta,
+        residuals,
+        scale,
+        iterations,
+    })
+}
+
+/// Iteratively reweighted least squares from the OLS fit. `weight(u)` maps a residual in
+/// units of the current robust scale to its weight.
+fn irls(prices_a: &[f64], prices_b: &[f64], weight: fn(f64) -> f64) -> Result<(f64, f64, usize), Error> {
+    let mut weights = vec![1.0; prices_a.len()];
+    let (mut alpha, mut beta) = weighted_fit(prices_a, prices_b, &weights)?;
+    for iteration in 1..=MAX_ITERATIONS {
+        let residuals = residuals(prices_a, prices_b, alpha, beta);
+        let scale = mad_scale(&residuals);
+        if scale == 0.0 {
+            // More than half the bars lie on the line already
+            return Ok((alpha, beta, iteration - 1));
+        }
+        for (w, r) in weights.iter_mut().zip(&residuals) {
+            *w = weight(r / scale);
+        }
+        let (next_alpha, next_beta) = weighted_fit(prices_a, prices_b, &weights)?;
+        let converged = (next_beta - beta).abs() <= TOLERANCE * (1.0 + beta.abs())
+            && (next_alpha - alpha).abs() <= TOLERANCE * (1.0 + alpha.abs());
+        alpha = next_alpha;
+        beta = next_beta;
+        if converged {
+            return Ok((alpha, beta, iteration));
+        }
+    }
+    Ok((alpha, beta, MAX_ITERATIONS))
+}
+
+fn huber_weight(u: f64) -> f64 {
+    if u.abs() <= HUBER_K {
+        1.0
+    } else {
+        HUBER_K / u.abs()
+    }
+}
+
+/// `1 / |u|`, floored so that bars already on the line do not get an infinite weight.
+fn lad_weight(u: f64) -> f64 {
+    1.0 / u.abs().max(1e-8)
+}
+
+/// Weighted least squares of `a` on `b` with centred two-pass sums.
+fn weighted_fit(prices_a: &[f64], prices_b: &[f64], weights: &[f64]) -> Result<(f64, f64), Error> {
+    let total: f64 = weights.iter().sum();
+    let mean_a = prices_a.iter().zip(weights).map(|(a, w)| w * a).sum::<f64>() / total;
+    let mean_b = prices_b.iter().zip(weights).map(|(b, w)| w * b).sum::<f64>() / total;
+    let (mut s_ab, mut s_bb) = (0.0, 0.0);
+    for ((a, b), w) in prices_a.iter().zip(prices_b).zip(weights) {
+        s_ab += w * (a - mean_a) * (b - mean_b);
+        s_bb += w * (b - mean_b) * (b - mean_b);
+    }
+    if s_bb.is_nan() || s_bb <= 0.0 || !total.is_finite() {
+        return Err(Error::SingularDesign);
+    }
+    let beta = s_ab / s_bb;
+    Ok((mean_a - beta * mean_b, beta))
+}
+
+/// Median of the slopes between every two bars with different B prices, collected in
+/// `slopes`.
+fn theil_sen(prices_a: &[f64], prices_b: &[f64], slopes: &mut Vec<f64>) -> Result<(f64, f64), Error> {
+    let n = prices_a.len();
+    check_theil_sen_size(n)?;
+    slopes.clear();
+    slopes.reserve(n * (n - 1) / 2);
+    for i in 0..n {
+        for j in i + 1..n {
+            let db = prices_b[j] - prices_b[i];
+            if db != 0.0 {
+                slopes.push((prices_a[j] - prices_a[i]) / db);
+            }
+        }
+    }
+    if slopes.is_empty() {
+        return Err(Error::SingularDesign);
+    }
+    let beta = median(slopes);
+    let mut intercepts: Vec<f64> = prices_a.iter().zip(prices_b).map(|(a, b)| a - beta * b).collect();
+    Ok((median(&mut intercepts), beta))
+}
+
+fn check_theil_sen_size(bars: usize) -> Result<(), Error> {
+    if bars > MAX_THEIL_SEN_BARS {
+        return Err(Error::InvalidOption(format!(
+            "Theil-Sen is limited to {} bars, got {}; use \"huber\" or \"lad\" for longer samples",
+            MAX_THEIL_SEN_BARS, bars
+        )));
+    }
+    Ok(())
+}
+
+fn residuals(prices_a: &[f64], prices_b: &[f64], alpha: f64, beta: f64) -> Vec<f64> {
+    prices_a.iter().zip(prices_b).map(|(a, b)| a - (alpha + beta * b)).collect()
+}
+
+fn mad_scale(residuals: &[f64]) -> f64 {
+    let mut deviations = residuals.to_vec();
+    let centre = median(&mut deviations);
+    for d in deviations.iter_mut() {
+        *d = (*d - centre).abs();
+    }
+    MAD_SCALE * median(&mut deviations)
+}
+
+/// Median of a non-empty slice, reordering it.
+fn median(values: &mut [f64]) -> f64 {
+    let len = values.len();
+    let (below, upper, _) = values.select_nth_unstable_by(len / 2, f64::total_cmp);
+    let upper = *upper;
+    if !len.is_multiple_of(2) {
+        upper
+    } else {
+        let lower = below.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        0.5 * (lower + upper)
+    }
+}
+
+fn classify(residuals: &[f64], scale: f64) -> (Vec<f64>, Vec<bool>) {
+    residuals.iter().map(|&r| classify_one(r, scale)).unzip()
+}
+
+/// Huber weight and outlier flag of one residual; with a zero scale only exact fits count
+/// as inliers.
+fn classify_one(residual: f64, scale: f64) -> (f64, bool) {
+    if scale == 0.0 {
+        return if residual == 0.0 { (1.0, false) } else { (0.0, true) };
+    }
+    let u = residual / scale;
+    (huber_weight(u), u.abs() > OUTLIER_THRESHOLD)
+}
+
+fn bool_array(flags: &[bool]) -> JsValue {
+    flags
+        .iter()
+        .map(|&flag| JsValue::from_bool(flag))
+        .collect::<js_sys::Array>()
+        .into()
+}
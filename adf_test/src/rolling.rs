@@ -0,0 +1,426 @@
+//! ADF statistics over rolling or expanding windows in one call.
+//!
+//! Every lag candidate of a window is fitted on the same rows, so all of them can be solved
+//! from one cross-product matrix of the widest design `[deterministic, y_{t-1}, lags, dy]`.
+//! That matrix is updated by adding the rows that enter the window and subtracting the rows
+//! that leave it, and rebuilt from scratch periodically to stop rounding from accumulating.
+//! The data are standardised first; the ADF statistic does not depend on the location and
+//! scale of the series (only the scale, without a constant) nor on where the trend starts,
+//! so the trend can be counted globally instead of restarting in each window.
+//!
+//! This is the one ADF path that forms `X'X` instead of going through [`crate::ols`], and
+//! only for speed: a QR fit costs `O(window * k^2)` per window and candidate lag, an update
+//! of the cached matrix `O(step * k^2)` per window. The squared condition number is kept
+//! in check by the standardisation and by scaling each candidate's matrix to a unit
+//! diagonal before the Cholesky factorisation; windows whose pivots are still too small
+//! are refitted with the QR solver of [`adf_test`].
+
+use nalgebra::{DMatrix, DVector};
+use wasm_bindgen::prelude::*;
+
+use crate::error::{self, Error};
+use crate::lag_selection::{self, LagCandidate, LagCriterion};
+use crate::regression::Regression;
+use crate::{adf_test, bootstrap, mackinnon, AdfOptions};
+
+/// Smallest pivot of the Cholesky factor, relative to the diagonal it came from, that is
+/// trusted before falling back to QR.
+const PIVOT_TOLERANCE: f64 = 1e-10;
+
+#[wasm_bindgen]
+pub struct RollingAdfResult {
+    window_ends: Vec<u32>,
+    test_statistics: Vec<f64>,
+    p_values: Vec<f64>,
+    lags: Vec<u32>,
+    stationary: Vec<bool>,
+}
+
+#[wasm_bindgen]
+impl RollingAdfResult {
+    /// Index of the last observation of each window
+    #[wasm_bindgen(getter)]
+    pub fn window_ends(&self) -> Vec<u32> {
+        self.window_ends.clone()
+    }
+
+    /// ADF statistic of each window; NaN where the test could not be computed
+    #[wasm_bindgen(getter)]
+    pub fn test_statistics(&self) -> Vec<f64> {
+        self.test_statistics.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn p_values(&self) -> Vec<f64> {
+        self.p_values.clone()
+    }
+
+    /// Lagged differences chosen in each window
+    #[wasm_bindgen(getter)]
+    pub fn lags(&self) -> Vec<u32> {
+        self.lags.clone()
+    }
+
+    /// Array of booleans, one per window
+    #[wasm_bindgen(getter)]
+    pub fn is_stationary(&self) -> JsValue {
+        self.stationary
+            .iter()
+            .map(|&flag| JsValue::from_bool(flag))
+            .collect::<js_sys::Array>()
+            .into()
+    }
+}
+
+impl RollingAdfResult {
+    pub fn stationary(&self) -> &[bool] {
+        &self.stationary
+    }
+
+    pub fn len(&self) -> usize {
+        self.window_ends.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window_ends.is_empty()
+    }
+}
+
+/// ADF test on every `window`-bar window, moving `step` bars at a time, configured like
+/// [`crate::calculate_adf_test`].
+#[wasm_bindgen]
+pub fn calculate_rolling_adf(
+    data: Vec<f64>,
+    window: usize,
+    step: usize,
+    options: &AdfOptions,
+) -> Result<RollingAdfResult, JsError> {
+    Ok(rolling_adf(&data, window, step, options)?)
+}
+
+/// ADF test on the windows `data[0..min_window]`, `data[0..min_window + step]`, ...
+#[wasm_bindgen]
+pub fn calculate_expanding_adf(
+    data: Vec<f64>,
+    min_window: usize,
+    step: usize,
+    options: &AdfOptions,
+) -> Result<RollingAdfResult, JsError> {
+    Ok(expanding_adf(&data, min_window, step, options)?)
+}
+
+pub fn rolling_adf(data: &[f64], window: usize, step: usize, options: &AdfOptions) -> Result<RollingAdfResult, Error> {
+    validate_windows(data, window, step, options.regression())?;
+    let windows = (0..=data.len() - window).step_by(step).map(|start| (start, start + window));
+    Ok(WindowedAdf::new(data, options).run(windows))
+}
+
+pub fn expanding_adf(data: &[f64], min_window: usize, step: usize, options: &AdfOptions) -> Result<RollingAdfResult, Error> {
+    validate_windows(data, min_window, step, options.regression())?;
+    let windows = (min_window..=data.len()).step_by(step).map(|end| (0, end));
+    Ok(WindowedAdf::new(data, options).run(windows))
+}
+
+fn validate_windows(data: &[f64], window: usize, step: usize, regression: Regression) -> Result<(), Error> {
+    if step == 0 {
+        return Err(Error::InvalidOption("step must be at least 1".to_string()));
+    }
+    let required = crate::min_observations(regression);
+    if window < required {
+        return Err(Error::InvalidOption(format!(
+            "window of {} bars is shorter than the {} the ADF regression needs",
+            window, required
+        )));
+    }
+    error::validate_series(data, window)
+}
+
+struct WindowedAdf<'a> {
+    data: &'a [f64],
+    options: &'a AdfOptions,
+    regression: Regression,
+    /// Standardised levels and their first differences
+    levels: Vec<f64>,
+    diffs: Vec<f64>,
+    trend_center: f64,
+    trend_scale: f64,
+    cache: Option<CrossProducts>,
+}
+
+/// Cross-products of the design rows `lo..hi` (indexed by the difference they explain)
+/// with `max_lags` lagged differences.
+struct CrossProducts {
+    max_lags: usize,
+    lo: usize,
+    hi: usize,
+    matrix: DMatrix<f64>,
+    rows_since_rebuild: usize,
+}
+
+struct Fit {
+    test_statistic: f64,
+    ssr: f64,
+    last_t: f64,
+}
+
+impl<'a> WindowedAdf<'a> {
+    fn new(data: &'a [f64], options: &'a AdfOptions) -> Self {
+        let regression = options.regression();
+        let n = data.len() as f64;
+        let mean = data.iter().sum::<f64>() / n;
+        let scale = (data.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+        // Shifting the levels changes the statistic when there is no constant
+        let center = if regression == Regression::NoConstant { 0.0 } else { mean };
+        let scale = if scale > 0.0 { scale } else { 1.0 };
+        let levels: Vec<f64> = data.iter().map(|v| (v - center) / scale).collect();
+        let diffs = levels.windows(2).map(|w| w[1] - w[0]).collect();
+
+        WindowedAdf {
+            data,
+            options,
+            regression,
+            levels,
+            diffs,
+            trend_center: n / 2.0,
+            trend_scale: (n / 2.0).max(1.0),
+            cache: None,
+        }
+    }
+
+    fn run(mut self, windows: impl Iterator<Item = (usize, usize)>) -> RollingAdfResult {
+        let mut result = RollingAdfResult {
+            window_ends: Vec::new(),
+            test_statistics: Vec::new(),
+            p_values: Vec::new(),
+            lags: Vec::new(),
+            stationary: Vec::new(),
+        };
+
+        for (start, end) in windows {
+            let outcome = self
+                .window(start, end)
+                .or_else(|| self.exact(start, end))
+                .unwrap_or((f64::NAN, f64::NAN, 0, false));
+            result.window_ends.push((end - 1) as u32);
+            result.test_statistics.push(outcome.0);
+            result.p_values.push(outcome.1);
+            result.lags.push(outcome.2);
+            result.stationary.push(outcome.3);
+        }
+        result
+    }
+
+    /// The QR path, for windows the normal equations cannot be trusted with.
+    fn exact(&self, start: usize, end: usize) -> Option<(f64, f64, u32, bool)> {
+        adf_test(&self.data[start..end], self.options)
+            .ok()
+            .map(|r| (r.test_statistic, r.p_value, r.optimal_lags, r.is_stationary))
+    }
+
+    /// Statistic, p-value, lag and stationarity of `data[start..end]` from the cached
+    /// cross-products, or `None` to fall back to [`Self::exact`].
+    fn window(&mut self, start: usize, end: usize) -> Option<(f64, f64, u32, bool)> {
+        let n = end - start;
+        let max_lags = crate::determine_max_lags(n, self.options.max_lags(), self.regression).ok()?;
+        let min_lags = match self.options.autolag() {
+            LagCriterion::Fixed => max_lags,
+            _ => 0,
+        };
+
+        // Common sample of all candidates: differences start + max_lags .. end - 1
+        let (lo, hi) = (start + max_lags, end - 1);
+        self.update(max_lags, lo, hi);
+        let cache = self.cache.as_ref()?;
+        let nobs = hi - lo;
+
+        let mut candidates = Vec::new();
+        for lags in min_lags..=max_lags {
+            let columns = self.columns(lags);
+            if nobs <= columns.len() {
+                continue;
+            }
+            let sub = DMatrix::from_fn(columns.len() + 1, columns.len() + 1, |i, j| {
+                cache.matrix[(self.column_or_y(&columns, i, max_lags), self.column_or_y(&columns, j, max_lags))]
+            });
+            let fit = solve(&sub, nobs, self.n_trend())?;
+            candidates.push(LagCandidate {
+                lags,
+                ssr: fit.ssr,
+                nobs,
+                n_params: columns.len(),
+                last_lag_t: fit.last_t,
+            });
+        }
+        let lags = lag_selection::select_lag(self.options.autolag(), &candidates)?;
+
+        // Re-estimate the chosen lag with the extra rows its shorter lag length allows
+        let columns = self.columns(lags);
+        let mut sub = DMatrix::from_fn(columns.len() + 1, columns.len() + 1, |i, j| {
+            cache.matrix[(self.column_or_y(&columns, i, max_lags), self.column_or_y(&columns, j, max_lags))]
+        });
+        let mut row = vec![0.0; self.width(lags)];
+        for d in start + lags..lo {
+            self.fill_row(d, lags, &mut row);
+            add_outer(&mut sub, &row, 1.0);
+        }
+        let nobs = hi - (start + lags);
+        if nobs <= columns.len() {
+            return None;
+        }
+        let fit = solve(&sub, nobs, self.n_trend())?;
+
+        let (p_value, critical_values) = match self.options.bootstrap() {
+            Some(settings) => bootstrap::bootstrap_p_value(
+                &self.data[start..end],
+                self.regression,
+                lags,
+                fit.test_statistic,
+                &settings,
+            )
+            .ok()?,
+            None => (
+                mackinnon::mackinnonp(fit.test_statistic, self.regression),
+                mackinnon::critical_values(self.regression, nobs),
+            ),
+        };
+        let is_stationary = crate::determine_stationarity(fit.test_statistic, &critical_values);
+        Some((fit.test_statistic, p_value, lags as u32, is_stationary))
+    }
+
+    fn n_trend(&self) -> usize {
+        self.regression.n_terms()
+    }
+
+    /// Columns of the design with `lags` lagged differences, plus the dependent variable.
+    fn width(&self, lags: usize) -> usize {
+        self.n_trend() + lags + 2
+    }
+
+    /// Indices into the `max_lags` cross-product matrix of the regressors with `lags` lags.
+    fn columns(&self, lags: usize) -> Vec<usize> {
+        (0..self.n_trend() + 1 + lags).collect()
+    }
+
+    /// Maps row/column `i` of a candidate's sub-matrix, where the last one is the dependent
+    /// variable, to the full matrix.
+    fn column_or_y(&self, columns: &[usize], i: usize, max_lags: usize) -> usize {
+        columns.get(i).copied().unwrap_or(self.width(max_lags) - 1)
+    }
+
+    /// Design row explaining difference `d`: deterministic terms, y_{d}, the previous
+    /// `lags` differences and the difference itself.
+    fn fill_row(&self, d: usize, lags: usize, row: &mut [f64]) {
+        let n_trend = self.n_trend();
+        let t = (d as f64 - self.trend_center) / self.trend_scale;
+        for (k, value) in row.iter_mut().enumerate().take(n_trend) {
+            *value = self.regression.term(k, t);
+        }
+        row[n_trend] = self.levels[d];
+        for j in 1..=lags {
+            row[n_trend + j] = self.diffs[d - j];
+        }
+        row[n_trend + lags + 1] = self.diffs[d];
+    }
+
+    /// Moves the cached cross-products to rows `lo..hi` with `max_lags` lags.
+    fn update(&mut self, max_lags: usize, lo: usize, hi: usize) {
+        let reusable = self.cache.as_ref().is_some_and(|c| {
+            c.max_lags == max_lags && c.lo < hi && lo < c.hi && c.rows_since_rebuild < 4 * (hi - lo).max(1)
+        });
+        let width = self.width(max_lags);
+        let mut row = vec![0.0; width];
+
+        if !reusable {
+            let mut matrix = DMatrix::zeros(width, width);
+            for d in lo..hi {
+                self.fill_row(d, max_lags, &mut row);
+                add_outer(&mut matrix, &row, 1.0);
+            }
+            self.cache = Some(CrossProducts {
+                max_lags,
+                lo,
+                hi,
+                matrix,
+                rows_since_rebuild: 0,
+            });
+            return;
+        }
+
+        let mut cache = self.cache.take().unwrap();
+        let mut apply = |range: std::ops::Range<usize>, sign: f64, cache: &mut CrossProducts| {
+            for d in range {
+                self.fill_row(d, max_lags, &mut row);
+                add_outer(&mut cache.matrix, &row, sign);
+                cache.rows_since_rebuild += 1;
+            }
+        };
+        if lo > cache.lo {
+            apply(cache.lo..lo, -1.0, &mut cache);
+        } else {
+            apply(lo..cache.lo, 1.0, &mut cache);
+        }
+        if hi > cache.hi {
+            apply(cache.hi..hi, 1.0, &mut cache);
+        } else {
+            apply(hi..cache.hi, -1.0, &mut cache);
+        }
+        cache.lo = lo;
+        cache.hi = hi;
+        self.cache = Some(cache);
+    }
+}
+
+fn add_outer(matrix: &mut DMatrix<f64>, row: &[f64], sign: f64) {
+    for (i, a) in row.iter().enumerate() {
+        for (j, b) in row.iter().enumerate() {
+            matrix[(i, j)] += sign * a * b;
+        }
+    }
+}
+
+/// Solves the normal equations in `sub` (regressors first, dependent variable last) for a
+/// regression on `nobs` rows whose coefficient of interest sits at `gamma_index`.
+///
+/// The cross-products are first scaled to a unit diagonal, so that the pivot test does not
+/// depend on the units of the columns, and the variances come from a triangular solve
+/// rather than an explicit inverse: with `X'X = L L'`, the `i`-th diagonal of `(X'X)^-1`
+/// is `|L^-1 e_i|^2`.
+fn solve(sub: &DMatrix<f64>, nobs: usize, gamma_index: usize) -> Option<Fit> {
+    let k = sub.nrows() - 1;
+    let scales: Vec<f64> = (0..=k).map(|i| sub[(i, i)].sqrt()).collect();
+    if scales.iter().any(|&s| s.is_nan() || s == 0.0) {
+        return None;
+    }
+    let scaled = DMatrix::from_fn(k + 1, k + 1, |i, j| sub[(i, j)] / (scales[i] * scales[j]));
+    let xtx = scaled.view((0, 0), (k, k)).into_owned();
+    let xty = scaled.view((0, k), (k, 1)).into_owned();
+
+    let cholesky = xtx.cholesky()?;
+    let l = cholesky.l_dirty();
+    if (0..k).any(|i| l[(i, i)] * l[(i, i)] < PIVOT_TOLERANCE) {
+        return None;
+    }
+    let params = cholesky.solve(&xty);
+    // y'y is 1 after scaling
+    let ssr = 1.0 - (params.transpose() * &xty)[(0, 0)];
+    // A near-perfect fit cannot be resolved from the cross-products
+    if ssr.is_nan() || ssr <= PIVOT_TOLERANCE {
+        return None;
+    }
+    let sigma2 = ssr / (nobs - k) as f64;
+    // The t-statistics do not depend on the column scales
+    let t = |i: usize| {
+        let mut unit = DVector::zeros(k);
+        unit[i] = 1.0;
+        let w = l.solve_lower_triangular(&unit)?;
+        Some(params[i] / (sigma2 * w.norm_squared()).sqrt())
+    };
+
+    let test_statistic = t(gamma_index)?;
+    let last_t = t(k - 1)?;
+    test_statistic.is_finite().then_some(Fit {
+        test_statistic,
+        ssr: ssr * scales[k] * scales[k],
+        last_t,
+    })
+}
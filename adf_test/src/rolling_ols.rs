@@ -0,0 +1,82 @@
+//! Rolling OLS hedge ratio, replacing the worker's `calculateHedgeRatio`, which refits the
+//! whole window for every bar.
+//!
+//! Bar `i` regresses A on B over bars `max(0, i - window + 1)..=i`, so the first
+//! `window - 1` bars use the shorter windows available to them, as the worker does. A
+//! window whose B prices do not vary gets the worker's fallback of beta = 1 and alpha = 0.
+//! Each bar's sums come from the previous bar's by adding the new bar and dropping the one
+//! that left.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::hedge::{self, HedgeSeries};
+
+#[wasm_bindgen]
+pub struct RollingOlsResult {
+    series: HedgeSeries,
+    r_squared: Vec<f64>,
+    /// Length of the full window
+    pub window: usize,
+}
+
+#[wasm_bindgen]
+impl RollingOlsResult {
+    /// Alpha, beta and spread `a - (alpha + beta * b)` for each bar
+    #[wasm_bindgen(getter)]
+    pub fn series(&self) -> HedgeSeries {
+        self.series.clone()
+    }
+
+    /// R-squared of each bar's window; NaN where either leg is constant over it
+    #[wasm_bindgen(getter)]
+    pub fn r_squared(&self) -> Vec<f64> {
+        self.r_squared.clone()
+    }
+}
+
+impl RollingOlsResult {
+    pub fn hedge_series(&self) -> &HedgeSeries {
+        &self.series
+    }
+}
+
+/// Rolling OLS of `prices_a` on `prices_b` over `window` bars.
+#[wasm_bindgen]
+pub fn calculate_rolling_ols(prices_a: Vec<f64>, prices_b: Vec<f64>, window: usize) -> Result<RollingOlsResult, JsError> {
+    Ok(rolling_ols(&prices_a, &prices_b, window)?)
+}
+
+pub fn rolling_ols(prices_a: &[f64], prices_b: &[f64], window: usize) -> Result<RollingOlsResult, Error> {
+    if window < 2 {
+        return Err(Error::InvalidOption(format!("window must be at least 2 bars, got {}", window)));
+    }
+    hedge::validate_pair(prices_a, prices_b, 1)?;
+
+    let n = prices_a.len();
+    let mut alphas = Vec::with_capacity(n);
+    let mut betas = Vec::with_capacity(n);
+    let mut r_squared = Vec::with_capacity(n);
+    hedge::for_each_window(prices_a, prices_b, window, |m| {
+        if m.s_bb == 0.0 {
+            alphas.push(0.0);
+            betas.push(1.0);
+            r_squared.push(f64::NAN);
+            return;
+        }
+        let beta = m.s_ab / m.s_bb;
+        alphas.push(m.mean_a - beta * m.mean_b);
+        betas.push(beta);
+        r_squared.push(if m.s_aa == 0.0 {
+            f64::NAN
+        } else {
+            (m.s_ab * m.s_ab / (m.s_aa * m.s_bb)).min(1.0)
+        });
+    });
+
+    Ok(RollingOlsResult {
+        series: HedgeSeries::new(prices_a, prices_b, alphas, betas),
+        r_squared,
+        window,
+    })
+}
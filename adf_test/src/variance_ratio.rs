@@ -0,0 +1,174 @@
+//! Lo and MacKinlay (1988) variance ratio test and the Chow and Denning (1993) joint test
+//! over several horizons. Under a random walk the variance of q-period changes is q times
+//! the one-period variance; a ratio below one means q-period moves partly cancel out, which
+//! is what a mean-reverting spread looks like.
+//!
+//! The test is applied to the changes of the series as given, so pass log prices for
+//! returns and the raw spread or ratio otherwise.
+
+use wasm_bindgen::prelude::*;
+
+use crate::distributions::erfc;
+use crate::error::{self, Error};
+
+/// Horizons tested when none are given.
+pub const DEFAULT_HORIZONS: [usize; 4] = [2, 4, 8, 16];
+
+#[wasm_bindgen]
+pub struct VarianceRatioResult {
+    horizons: Vec<u32>,
+    variance_ratios: Vec<f64>,
+    z_statistics: Vec<f64>,
+    robust_z_statistics: Vec<f64>,
+    p_values: Vec<f64>,
+    robust_p_values: Vec<f64>,
+    /// Largest |z| over the horizons, homoskedastic
+    pub chow_denning: f64,
+    pub chow_denning_p_value: f64,
+    /// Largest |z| over the horizons, heteroskedasticity-robust
+    pub chow_denning_robust: f64,
+    pub chow_denning_robust_p_value: f64,
+    /// The robust joint test rejects the random walk at 5% and the variance ratio at the
+    /// deciding horizon is below one
+    pub is_mean_reverting: bool,
+}
+
+#[wasm_bindgen]
+impl VarianceRatioResult {
+    #[wasm_bindgen(getter)]
+    pub fn horizons(&self) -> Vec<u32> {
+        self.horizons.clone()
+    }
+
+    /// VR(q) for each horizon
+    #[wasm_bindgen(getter)]
+    pub fn variance_ratios(&self) -> Vec<f64> {
+        self.variance_ratios.clone()
+    }
+
+    /// z(q) under homoskedastic increments
+    #[wasm_bindgen(getter)]
+    pub fn z_statistics(&self) -> Vec<f64> {
+        self.z_statistics.clone()
+    }
+
+    /// z*(q), robust to heteroskedastic increments
+    #[wasm_bindgen(getter)]
+    pub fn robust_z_statistics(&self) -> Vec<f64> {
+        self.robust_z_statistics.clone()
+    }
+
+    /// Two-sided normal p-values of z(q)
+    #[wasm_bindgen(getter)]
+    pub fn p_values(&self) -> Vec<f64> {
+        self.p_values.clone()
+    }
+
+    /// Two-sided normal p-values of z*(q)
+    #[wasm_bindgen(getter)]
+    pub fn robust_p_values(&self) -> Vec<f64> {
+        self.robust_p_values.clone()
+    }
+}
+
+/// Variance ratio tests of `data` at each horizon in `horizons` (2, 4, 8 and 16 bars when
+/// empty).
+#[wasm_bindgen]
+pub fn calculate_variance_ratio_test(data: Vec<f64>, horizons: Vec<u32>) -> Result<VarianceRatioResult, JsError> {
+    let horizons: Vec<usize> = horizons.into_iter().map(|q| q as usize).collect();
+    Ok(variance_ratio_test(&data, &horizons)?)
+}
+
+pub fn variance_ratio_test(data: &[f64], horizons: &[usize]) -> Result<VarianceRatioResult, Error> {
+    let horizons = if horizons.is_empty() { &DEFAULT_HORIZONS[..] } else { horizons };
+    let longest = horizons.iter().copied().max().unwrap_or(2);
+    if let Some(q) = horizons.iter().find(|&&q| q < 2) {
+        return Err(Error::InvalidOption(format!("variance ratio horizons must be at least 2, got {}", q)));
+    }
+    // At least two non-overlapping q-period changes at the longest horizon
+    error::validate_series(data, 2 * longest + 1)?;
+
+    let increments: Vec<f64> = data.windows(2).map(|w| w[1] - w[0]).collect();
+    let n = increments.len() as f64;
+    let mu = (data[data.len() - 1] - data[0]) / n;
+    let deviations: Vec<f64> = increments.iter().map(|x| x - mu).collect();
+    let squares: Vec<f64> = deviations.iter().map(|d| d * d).collect();
+    let sum_squares: f64 = squares.iter().sum();
+    let sigma_a = sum_squares / (n - 1.0);
+
+    let mut result = VarianceRatioResult {
+        horizons: horizons.iter().map(|&q| q as u32).collect(),
+        variance_ratios: Vec::with_capacity(horizons.len()),
+        z_statistics: Vec::with_capacity(horizons.len()),
+        robust_z_statistics: Vec::with_capacity(horizons.len()),
+        p_values: Vec::with_capacity(horizons.len()),
+        robust_p_values: Vec::with_capacity(horizons.len()),
+        chow_denning: 0.0,
+        chow_denning_p_value: 1.0,
+        chow_denning_robust: 0.0,
+        chow_denning_robust_p_value: 1.0,
+        is_mean_reverting: false,
+    };
+
+    for &q in horizons {
+        let qf = q as f64;
+        // Overlapping q-period changes with Lo and MacKinlay's unbiased normalisation
+        let m = qf * (n - qf + 1.0) * (1.0 - qf / n);
+        let sigma_c: f64 = data
+            .windows(q + 1)
+            .map(|w| (w[q] - w[0] - qf * mu).powi(2))
+            .sum::<f64>()
+            / m;
+        let variance_ratio = sigma_c / sigma_a;
+
+        // Asymptotic variances of VR(q) - 1; theta / n is the heteroskedasticity-consistent one
+        let homoskedastic_variance = 2.0 * (2.0 * qf - 1.0) * (qf - 1.0) / (3.0 * qf * n);
+        let theta: f64 = (1..q)
+            .map(|j| {
+                let weight = 2.0 * (qf - j as f64) / qf;
+                let delta: f64 = n * (j..squares.len()).map(|t| squares[t] * squares[t - j]).sum::<f64>()
+                    / (sum_squares * sum_squares);
+                weight * weight * delta
+            })
+            .sum();
+
+        let z = (variance_ratio - 1.0) / homoskedastic_variance.sqrt();
+        let robust_z = (variance_ratio - 1.0) / (theta / n).sqrt();
+        if !z.is_finite() || !robust_z.is_finite() {
+            return Err(Error::SingularDesign);
+        }
+        result.variance_ratios.push(variance_ratio);
+        result.z_statistics.push(z);
+        result.robust_z_statistics.push(robust_z);
+        result.p_values.push(two_sided_p_value(z));
+        result.robust_p_values.push(two_sided_p_value(robust_z));
+    }
+
+    let k = horizons.len();
+    (result.chow_denning, result.chow_denning_p_value) = chow_denning(&result.z_statistics, k);
+    (result.chow_denning_robust, result.chow_denning_robust_p_value) = chow_denning(&result.robust_z_statistics, k);
+
+    let deciding = result
+        .robust_z_statistics
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    result.is_mean_reverting =
+        result.chow_denning_robust_p_value < 0.05 && result.variance_ratios[deciding] < 1.0;
+    Ok(result)
+}
+
+/// Largest |z| and its p-value under the Sidak bound for `k` horizons, as proposed by
+/// Chow and Denning (the studentised maximum modulus with infinite degrees of freedom).
+fn chow_denning(z_statistics: &[f64], k: usize) -> (f64, f64) {
+    let statistic = z_statistics.iter().map(|z| z.abs()).fold(0.0, f64::max);
+    let single = two_sided_p_value(statistic);
+    let p_value = 1.0 - (1.0 - single).powi(k as i32);
+    (statistic, p_value.clamp(0.0, 1.0))
+}
+
+fn two_sided_p_value(z: f64) -> f64 {
+    erfc(z.abs() / std::f64::consts::SQRT_2)
+}
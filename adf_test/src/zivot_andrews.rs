@@ -0,0 +1,217 @@
+//! Zivot and Andrews (1992) unit root test with one endogenous structural break. The ADF
+//! regression is augmented with a shift in the intercept, the trend or both at every
+//! candidate break date, and the most negative t-statistic on y_{t-1} is kept, so a spread
+//! that is stationary around a one-off level change is not mistaken for a random walk.
+
+use std::fmt;
+use std::str::FromStr;
+
+use nalgebra::{DMatrix, DVector};
+use wasm_bindgen::prelude::*;
+
+use crate::error::{self, Error};
+use crate::lag_selection::LagCriterion;
+use crate::ols;
+use crate::regression::Regression;
+use crate::CriticalValues;
+
+/// Share of the sample excluded at each end from the break search, as in Zivot and Andrews.
+const TRIM: f64 = 0.15;
+
+/// Which deterministic terms are allowed to change at the break.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BreakType {
+    /// "c": model A, a shift in the intercept
+    #[default]
+    Intercept,
+    /// "t": model B, a change in the trend slope
+    Trend,
+    /// "ct": model C, both
+    Both,
+}
+
+impl BreakType {
+    pub fn code(self) -> &'static str {
+        match self {
+            BreakType::Intercept => "c",
+            BreakType::Trend => "t",
+            BreakType::Both => "ct",
+        }
+    }
+
+    /// Asymptotic critical values from Zivot and Andrews (1992), Tables 2-4.
+    pub fn critical_values(self) -> CriticalValues {
+        let [one_percent, five_percent, ten_percent] = match self {
+            BreakType::Intercept => [-5.34, -4.80, -4.58],
+            BreakType::Trend => [-4.93, -4.42, -4.11],
+            BreakType::Both => [-5.57, -5.08, -4.82],
+        };
+        CriticalValues {
+            one_percent,
+            five_percent,
+            ten_percent,
+        }
+    }
+
+    fn shifts_intercept(self) -> bool {
+        self != BreakType::Trend
+    }
+
+    fn shifts_trend(self) -> bool {
+        self != BreakType::Intercept
+    }
+}
+
+impl FromStr for BreakType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "c" => Ok(BreakType::Intercept),
+            "t" => Ok(BreakType::Trend),
+            "ct" => Ok(BreakType::Both),
+            other => Err(Error::InvalidOption(format!(
+                "unknown break type \"{}\" (expected one of \"c\", \"t\", \"ct\")",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for BreakType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+#[wasm_bindgen]
+pub struct ZivotAndrewsResult {
+    /// Minimum t-statistic on y_{t-1} over all candidate breaks
+    pub test_statistic: f64,
+    /// Index of the last observation before the break. Each regime keeps at least 15% of the
+    /// series and of the regression rows.
+    pub break_index: u32,
+    /// Lagged differences, chosen on the unbroken "ct" ADF regression
+    pub lags: u32,
+    pub nobs: u32,
+    critical_values: CriticalValues,
+    pub is_stationary: bool,
+}
+
+#[wasm_bindgen]
+impl ZivotAndrewsResult {
+    #[wasm_bindgen(getter)]
+    pub fn critical_values(&self) -> JsValue {
+        self.critical_values.to_js()
+    }
+}
+
+impl ZivotAndrewsResult {
+    pub fn critical_value_table(&self) -> CriticalValues {
+        self.critical_values
+    }
+}
+
+/// Zivot-Andrews test with a break in the intercept ("c"), the trend ("t") or both ("ct").
+/// `autolag` and `max_lags` choose the lagged differences as in `AdfOptions`.
+#[wasm_bindgen]
+pub fn calculate_zivot_andrews_test(
+    data: Vec<f64>,
+    break_type: &str,
+    autolag: &str,
+    max_lags: Option<u32>,
+) -> Result<ZivotAndrewsResult, JsError> {
+    Ok(zivot_andrews_test(&data, break_type.parse()?, autolag.parse()?, max_lags)?)
+}
+
+pub fn zivot_andrews_test(
+    data: &[f64],
+    break_type: BreakType,
+    autolag: LagCriterion,
+    max_lags: Option<u32>,
+) -> Result<ZivotAndrewsResult, Error> {
+    let n = data.len();
+    error::validate_series(data, crate::min_observations(Regression::ConstantTrendSquared) * 2)?;
+
+    // Like statsmodels, the lag length is chosen once on the regression without a break
+    let lags = crate::search_lags(data, Regression::ConstantTrend, autolag, max_lags)?.lags();
+
+    // Delta y_t for t = lags + 1 .. n - 1, regressed on const, trend, the break dummies,
+    // y_{t-1} and the lagged differences
+    let diff: Vec<f64> = data.windows(2).map(|w| w[1] - w[0]).collect();
+    let first = lags + 1;
+    let nobs = n - first;
+    let n_break_terms = break_type.shifts_intercept() as usize + break_type.shifts_trend() as usize;
+    let gamma_index = 2 + n_break_terms;
+    let n_params = gamma_index + 1 + lags;
+    if nobs <= n_params + 1 {
+        return Err(Error::InsufficientData {
+            required: first + n_params + 2,
+            actual: n,
+        });
+    }
+
+    let y = DVector::from_fn(nobs, |i, _| diff[first + i - 1]);
+    let mut x = DMatrix::from_fn(nobs, n_params, |i, k| {
+        let t = first + i;
+        match k {
+            0 => 1.0,
+            1 => t as f64,
+            k if k == gamma_index => data[t - 1],
+            k if k > gamma_index => diff[t - 1 - (k - gamma_index)],
+            _ => 0.0,
+        }
+    });
+
+    // Candidate breaks respect the trimming both in the series and in the regression rows,
+    // which start `first` bars in once lagged differences are included
+    let Some((lowest, highest)) = break_range(n, first) else {
+        let required = (n + 1..).find(|&m| break_range(m, first).is_some()).unwrap_or(n + 1);
+        return Err(Error::InsufficientData { required, actual: n });
+    };
+
+    let mut best: Option<(f64, usize)> = None;
+    for break_index in lowest..=highest {
+        for i in 0..nobs {
+            let t = first + i;
+            let after = t > break_index;
+            let mut k = 2;
+            if break_type.shifts_intercept() {
+                x[(i, k)] = if after { 1.0 } else { 0.0 };
+                k += 1;
+            }
+            if break_type.shifts_trend() {
+                x[(i, k)] = if after { (t - break_index) as f64 } else { 0.0 };
+            }
+        }
+
+        let Ok(fit) = ols::ols(&x, &y) else { continue };
+        let statistic = fit.params[gamma_index] / fit.std_errors()[gamma_index];
+        if statistic.is_finite() && best.is_none_or(|(min, _)| statistic < min) {
+            best = Some((statistic, break_index));
+        }
+    }
+
+    let (test_statistic, break_index) = best.ok_or(Error::SingularDesign)?;
+    let critical_values = break_type.critical_values();
+
+    Ok(ZivotAndrewsResult {
+        test_statistic,
+        break_index: break_index as u32,
+        lags: lags as u32,
+        nobs: nobs as u32,
+        critical_values,
+        is_stationary: test_statistic < critical_values.five_percent,
+    })
+}
+
+/// First and last candidate break for a series of `n` bars whose regression rows start at
+/// bar `first`, or `None` if no break leaves both regimes enough bars. Each regime keeps
+/// at least `TRIM` of the series and of the regression rows, and at least one row.
+fn break_range(n: usize, first: usize) -> Option<(usize, usize)> {
+    let trim = (n as f64 * TRIM) as usize;
+    let row_trim = (((n - first) as f64 * TRIM) as usize).max(1);
+    let lowest = trim.max(first + row_trim - 1);
+    let highest = (n - 1).checked_sub(trim.max(row_trim))?;
+    (lowest <= highest).then_some((lowest, highest))
+}
@@ -0,0 +1,244 @@
+//! ADF against statsmodels' `adfuller` with fixed lags, transcribed below: the differences
+//! regressed on the deterministic terms, the lagged level and the lagged differences.
+
+use adf_test::mackinnon::{critical_values, mackinnonp};
+use adf_test::{adf_test, AdfOptions, LagCriterion, Regression};
+use nalgebra::{DMatrix, DVector};
+
+mod common;
+
+use common::normals;
+
+struct Fit {
+    params: Vec<f64>,
+    std_errors: Vec<f64>,
+    ssr: f64,
+    nobs: usize,
+    r_squared: f64,
+    condition_number: f64,
+}
+
+/// OLS through the normal equations on the `adfuller` design with `lags` lagged
+/// differences: `[const, t, t^2][..n_trend], y_{t-1}, dy_{t-1}, ..., dy_{t-lags}`, with the
+/// trend counted from 1 within the sample.
+fn adfuller_regression(y: &[f64], n_trend: usize, lags: usize) -> Fit {
+    let dy: Vec<f64> = y.windows(2).map(|w| w[1] - w[0]).collect();
+    let nobs = dy.len() - lags;
+    let x = DMatrix::from_fn(nobs, n_trend + 1 + lags, |i, k| {
+        let t = i + lags;
+        let trend = (i + 1) as f64;
+        match k {
+            _ if k < n_trend => trend.powi(k as i32),
+            _ if k == n_trend => y[t],
+            _ => dy[t - (k - n_trend)],
+        }
+    });
+    let lhs = DVector::from_fn(nobs, |i, _| dy[i + lags]);
+    let xtx = x.transpose() * &x;
+    let xtx_inv = xtx.clone().try_inverse().unwrap();
+    let params = &xtx_inv * x.transpose() * &lhs;
+    let resid = &lhs - &x * &params;
+    let ssr = resid.dot(&resid);
+    let s2 = ssr / (nobs - x.ncols()) as f64;
+    // statsmodels' rsquared: centred with a constant, uncentred without
+    let mean = if n_trend > 0 { lhs.mean() } else { 0.0 };
+    let tss: f64 = lhs.iter().map(|v| (v - mean).powi(2)).sum();
+    let eigenvalues = xtx.symmetric_eigenvalues();
+    Fit {
+        params: params.iter().copied().collect(),
+        std_errors: (0..x.ncols()).map(|k| (s2 * xtx_inv[(k, k)]).sqrt()).collect(),
+        ssr,
+        nobs,
+        r_squared: 1.0 - ssr / tss,
+        condition_number: (eigenvalues.max() / eigenvalues.min()).sqrt(),
+    }
+}
+
+/// A random walk with drift and a stationary AR(1) around a trend.
+fn series(n: usize) -> (Vec<f64>, Vec<f64>) {
+    let shocks = normals(n, 29);
+    let walk = shocks
+        .iter()
+        .scan(100.0, |level, e| {
+            *level += 0.05 + e;
+            Some(*level)
+        })
+        .collect();
+    let mut ar = 0.0;
+    let stationary = shocks
+        .iter()
+        .enumerate()
+        .map(|(i, e)| {
+            ar = 0.6 * ar + e;
+            20.0 + 0.03 * i as f64 + ar
+        })
+        .collect();
+    (walk, stationary)
+}
+
+fn fixed(regression: Regression, lags: u32) -> AdfOptions {
+    AdfOptions::default()
+        .with_regression(regression)
+        .with_autolag(LagCriterion::Fixed)
+        .with_max_lags(Some(lags))
+}
+
+const REGRESSIONS: [Regression; 4] = [
+    Regression::NoConstant,
+    Regression::Constant,
+    Regression::ConstantTrend,
+    Regression::ConstantTrendSquared,
+];
+
+#[test]
+fn matches_adfuller_for_every_regression() {
+    let (walk, stationary) = series(254);
+    for (name, data) in [("walk", &walk), ("stationary", &stationary)] {
+        for regression in REGRESSIONS {
+            for lags in [0, 3] {
+                let result = adf_test(data, &fixed(regression, lags)).unwrap();
+                let fit = adfuller_regression(data, regression.n_terms(), lags as usize);
+                let gamma = regression.n_terms();
+                let expected = fit.params[gamma] / fit.std_errors[gamma];
+                let case = format!("{} {} lags {}", name, regression, lags);
+                assert_eq!(result.optimal_lags, lags, "{}", case);
+                assert!(
+                    (result.test_statistic - expected).abs() < 1e-8 * expected.abs().max(1.0),
+                    "{}: {} vs {}",
+                    case,
+                    result.test_statistic,
+                    expected
+                );
+                assert_eq!(result.p_value, mackinnonp(result.test_statistic, regression), "{}", case);
+                assert_eq!(result.critical_value_table(), critical_values(regression, fit.nobs), "{}", case);
+            }
+        }
+    }
+    assert!(!adf_test(&walk, &fixed(Regression::Constant, 3)).unwrap().is_stationary);
+    assert!(adf_test(&stationary, &fixed(Regression::ConstantTrend, 3)).unwrap().is_stationary);
+}
+
+#[test]
+fn design_columns_follow_the_regression() {
+    let (walk, _) = series(254);
+    let expected: [&[&str]; 4] = [
+        &["gamma", "lag_1", "lag_2"],
+        &["const", "gamma", "lag_1", "lag_2"],
+        &["const", "trend", "gamma", "lag_1", "lag_2"],
+        &["const", "trend", "trend_squared", "gamma", "lag_1", "lag_2"],
+    ];
+    for (regression, names) in REGRESSIONS.into_iter().zip(expected) {
+        let report = adf_test(&walk, &fixed(regression, 2)).unwrap().report();
+        assert_eq!(report.param_names(), names, "{}", regression);
+        assert_eq!(report.params().len(), names.len(), "{}", regression);
+        assert_eq!(regression.code().parse::<Regression>().unwrap(), regression);
+    }
+    assert_eq!("nc".parse::<Regression>().unwrap(), Regression::NoConstant);
+    assert!("cttt".parse::<Regression>().is_err());
+}
+
+#[test]
+fn critical_values_follow_the_regression() {
+    let (walk, _) = series(254);
+    // 250 observations in the regression after three lags
+    let expected = [
+        (Regression::NoConstant, [-2.5747, -1.9421, -1.6158]),
+        (Regression::ConstantTrendSquared, [-4.4181, -3.8562, -3.5680]),
+    ];
+    for (regression, [one, five, ten]) in expected {
+        let cv = adf_test(&walk, &fixed(regression, 3)).unwrap().critical_value_table();
+        assert!((cv.one_percent - one).abs() < 1e-4, "{}: {}", regression, cv.one_percent);
+        assert!((cv.five_percent - five).abs() < 1e-4, "{}: {}", regression, cv.five_percent);
+        assert!((cv.ten_percent - ten).abs() < 1e-4, "{}: {}", regression, cv.ten_percent);
+    }
+    // More deterministic terms push the critical values further left
+    let five_percent: Vec<f64> = REGRESSIONS
+        .iter()
+        .map(|&regression| critical_values(regression, 250).five_percent)
+        .collect();
+    assert!(five_percent.windows(2).all(|w| w[1] < w[0]), "{:?}", five_percent);
+}
+
+#[test]
+fn report_matches_the_regression() {
+    let (walk, stationary) = series(254);
+    let close = |actual: f64, expected: f64| (actual - expected).abs() < 1e-8 * expected.abs().max(1.0);
+    for (name, data) in [("walk", &walk), ("stationary", &stationary)] {
+        for regression in [Regression::NoConstant, Regression::Constant, Regression::ConstantTrend] {
+            let result = adf_test(data, &fixed(regression, 2)).unwrap();
+            let report = result.report();
+            let fit = adfuller_regression(data, regression.n_terms(), 2);
+            let case = format!("{} {}", name, regression);
+            let gamma = regression.n_terms();
+            for k in 0..fit.params.len() {
+                assert!(close(report.params()[k], fit.params[k]), "{} param {}", case, k);
+                assert!(close(report.std_errors()[k], fit.std_errors[k]), "{} std error {}", case, k);
+                assert!(close(report.t_values()[k], fit.params[k] / fit.std_errors[k]), "{} t {}", case, k);
+            }
+            assert_eq!(report.gamma, report.params()[gamma], "{}", case);
+            assert_eq!(report.gamma_std_error, report.std_errors()[gamma], "{}", case);
+            assert_eq!(report.t_values()[gamma], result.test_statistic, "{}", case);
+            assert_eq!(report.nobs as usize, fit.nobs, "{}", case);
+            assert!(close(report.ssr, fit.ssr), "{}", case);
+            assert!(close(report.r_squared, fit.r_squared), "{}: {} vs {}", case, report.r_squared, fit.r_squared);
+            assert!(
+                (report.condition_number - fit.condition_number).abs() < 1e-6 * fit.condition_number,
+                "{}: {} vs {}",
+                case,
+                report.condition_number,
+                fit.condition_number
+            );
+
+            let n = fit.nobs as f64;
+            let k = fit.params.len() as f64;
+            let llf = -n / 2.0 * ((2.0 * std::f64::consts::PI).ln() + (fit.ssr / n).ln() + 1.0);
+            assert!(close(report.aic, -2.0 * llf + 2.0 * k), "{}", case);
+            assert!(close(report.bic, -2.0 * llf + n.ln() * k), "{}", case);
+            assert_eq!(result.aic_value, report.aic, "{}", case);
+        }
+    }
+
+    // Mean reversion at 1 + gamma per bar halves a deviation in ln(0.5) / ln(1 + gamma) bars
+    let report = adf_test(&stationary, &fixed(Regression::ConstantTrend, 0)).unwrap().report();
+    assert!(report.gamma > -1.0 && report.gamma < 0.0);
+    assert!((report.half_life - 0.5_f64.ln() / (1.0 + report.gamma).ln()).abs() < 1e-12);
+    assert!(report.half_life > 0.5 && report.half_life < 5.0, "{}", report.half_life);
+
+    // An explosive series never halves
+    let explosive: Vec<f64> = normals(120, 31)
+        .iter()
+        .scan(1.0, |level, e| {
+            *level = 1.03 * *level + 0.1 * e;
+            Some(*level)
+        })
+        .collect();
+    let report = adf_test(&explosive, &fixed(Regression::Constant, 0)).unwrap().report();
+    assert!(report.gamma > 0.0);
+    assert_eq!(report.half_life, f64::INFINITY);
+}
+
+#[test]
+fn stationarity_follows_the_finite_sample_critical_value() {
+    let window = |seed: u64| -> Vec<f64> {
+        let mut ar = 0.0;
+        normals(40, seed)
+            .iter()
+            .map(|e| {
+                ar = 0.6 * ar + e;
+                ar
+            })
+            .collect()
+    };
+    for seed in 100..160 {
+        let result = adf_test(&window(seed), &fixed(Regression::Constant, 1)).unwrap();
+        let five_percent = result.critical_value_table().five_percent;
+        assert_eq!(result.is_stationary, result.test_statistic < five_percent, "seed {}", seed);
+    }
+
+    // Between the 5% critical values at 38 observations (-2.94) and asymptotically (-2.86):
+    // the asymptotic p-value is just under 0.05, but the test does not reject
+    let result = adf_test(&window(135), &fixed(Regression::Constant, 1)).unwrap();
+    assert!((result.test_statistic + 2.8622).abs() < 1e-4, "{}", result.test_statistic);
+    assert!(result.p_value < 0.05);
+    assert!(!result.is_stationary);
+}
@@ -0,0 +1,53 @@
+//! Reproducibility of the bootstrap ADF p-values.
+
+use adf_test::{adf_test, AdfOptions, Bootstrap, BootstrapMethod};
+
+mod common;
+
+use common::Lcg;
+
+/// A random walk driven by a fixed linear congruential generator.
+fn random_walk(n: usize) -> Vec<f64> {
+    let mut rng = Lcg::new(12345);
+    let mut level = 100.0;
+    (0..n)
+        .map(|_| {
+            level += rng.centred();
+            level
+        })
+        .collect()
+}
+
+fn options(method: BootstrapMethod, seed: u64) -> AdfOptions {
+    AdfOptions::new().with_bootstrap(Some(Bootstrap::new(method, 199, seed)))
+}
+
+#[test]
+fn same_seed_gives_same_p_value() {
+    let data = random_walk(120);
+    for method in [BootstrapMethod::Sieve, BootstrapMethod::StationaryBlock] {
+        let first = adf_test(&data, &options(method, 7)).unwrap();
+        let second = adf_test(&data, &options(method, 7)).unwrap();
+        assert_eq!(first.p_value, second.p_value);
+        assert_eq!(first.critical_value_table(), second.critical_value_table());
+        assert!(first.p_value > 0.0 && first.p_value <= 1.0);
+    }
+}
+
+#[test]
+fn bootstrap_agrees_with_tables_on_a_random_walk() {
+    // With 2,000 sieve replications the Monte Carlo error of a p-value is at most
+    // sqrt(0.25 / 2000) = 0.011, and that of the 5% and 10% quantiles about 0.03
+    let data = random_walk(250);
+    let tabulated = adf_test(&data, &AdfOptions::new()).unwrap();
+    let settings = Bootstrap::new(BootstrapMethod::Sieve, 2000, 1);
+    let bootstrapped = adf_test(&data, &AdfOptions::new().with_bootstrap(Some(settings))).unwrap();
+    assert_eq!(tabulated.test_statistic, bootstrapped.test_statistic);
+    assert!((tabulated.p_value - bootstrapped.p_value).abs() < 0.03, "{} vs {}", bootstrapped.p_value, tabulated.p_value);
+
+    let tables = tabulated.critical_value_table();
+    let critical_values = bootstrapped.critical_value_table();
+    assert!((critical_values.five_percent - tables.five_percent).abs() < 0.05, "{:?}", critical_values);
+    assert!((critical_values.ten_percent - tables.ten_percent).abs() < 0.05, "{:?}", critical_values);
+    assert!(critical_values.one_percent < critical_values.five_percent);
+}
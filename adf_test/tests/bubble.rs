@@ -0,0 +1,85 @@
+//! SADF and GSADF computed from prefix sums must match the ADF regression run window by window.
+
+use adf_test::{adf_test, bubble_test, AdfOptions, LagCriterion};
+
+mod common;
+
+use common::{normals, Lcg};
+
+/// A random walk that turns explosive for its last 20 observations.
+fn series() -> Vec<f64> {
+    let mut rng = Lcg::new(2024);
+    let mut value = 10.0;
+    (0..90)
+        .map(|t| {
+            let shock = rng.centred();
+            value = if t >= 70 { 1.04 * value + shock } else { value + shock };
+            value
+        })
+        .collect()
+}
+
+fn windowed_adf(data: &[f64], lags: u32) -> f64 {
+    let options = AdfOptions::new().with_autolag(LagCriterion::Fixed).with_max_lags(Some(lags));
+    adf_test(data, &options).unwrap().test_statistic
+}
+
+#[test]
+fn statistics_match_window_by_window_adf() {
+    let data = series();
+    for lags in [0, 2] {
+        let result = bubble_test(&data, Some(20), lags, 20, 5).unwrap();
+        let min_window = result.min_window as usize;
+
+        let sadf = (min_window - 1..data.len())
+            .map(|r2| windowed_adf(&data[..=r2], lags as u32))
+            .fold(f64::NEG_INFINITY, f64::max);
+        assert!((result.sadf - sadf).abs() < 1e-8);
+
+        let bsadf = result.bsadf();
+        for r2 in min_window - 1..data.len() {
+            let expected = (0..=r2 + 1 - min_window)
+                .map(|r1| windowed_adf(&data[r1..=r2], lags as u32))
+                .fold(f64::NEG_INFINITY, f64::max);
+            assert!((bsadf[r2] - expected).abs() < 1e-8, "end {}", r2);
+        }
+        assert_eq!(result.gsadf, bsadf.iter().copied().fold(f64::NEG_INFINITY, f64::max));
+    }
+}
+
+#[test]
+fn prefix_sums_hold_at_price_levels() {
+    // A price around 50,000 that moves by a few tens per bar and turns explosive at the
+    // end: the level column is nearly collinear with the constant in every short window
+    let shocks = normals(90, 61);
+    let mut price = 50_000.0;
+    let data: Vec<f64> = shocks
+        .iter()
+        .enumerate()
+        .map(|(t, e)| {
+            price = if t >= 75 { 1.002 * price + 20.0 * e } else { price + 20.0 * e };
+            price
+        })
+        .collect();
+    for lags in [0, 2] {
+        let result = bubble_test(&data, Some(15), lags, 20, 5).unwrap();
+        let bsadf = result.bsadf();
+        for r2 in 14..data.len() {
+            let expected = (0..=r2 + 1 - 15)
+                .map(|r1| windowed_adf(&data[r1..=r2], lags as u32))
+                .fold(f64::NEG_INFINITY, f64::max);
+            assert!((bsadf[r2] - expected).abs() < 1e-8 * expected.abs().max(1.0), "lags {} end {}", lags, r2);
+        }
+    }
+}
+
+#[test]
+fn monte_carlo_critical_values_are_reproducible() {
+    let data = series();
+    let first = bubble_test(&data, None, 0, 50, 9).unwrap();
+    let second = bubble_test(&data, None, 0, 50, 9).unwrap();
+    assert_eq!(first.gsadf_critical_value_table(), second.gsadf_critical_value_table());
+    let bits = |values: Vec<f64>| values.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+    assert_eq!(bits(first.bsadf_critical_values()), bits(second.bsadf_critical_values()));
+    assert!(first.is_explosive);
+}
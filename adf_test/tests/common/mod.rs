@@ -0,0 +1,41 @@
+//! Reproducible random numbers for the integration tests.
+
+// Each test crate compiles this module and uses only part of it
+#![allow(dead_code)]
+
+/// 64-bit linear congruential generator with Knuth's MMIX constants.
+pub struct Lcg(u64);
+
+impl Lcg {
+    pub fn new(seed: u64) -> Self {
+        Lcg(seed)
+    }
+
+    fn next_bits(&mut self) -> u64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        self.0 >> 11
+    }
+
+    /// Uniform on [0, 1) from the top 53 bits.
+    pub fn uniform(&mut self) -> f64 {
+        self.next_bits() as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform on [-0.5, 0.5).
+    pub fn centred(&mut self) -> f64 {
+        self.uniform() - 0.5
+    }
+
+    /// Standard normal by Box-Muller, from uniforms kept off zero.
+    pub fn normal(&mut self) -> f64 {
+        let u = (self.next_bits() as f64 + 0.5) / (1u64 << 53) as f64;
+        let v = (self.next_bits() as f64 + 0.5) / (1u64 << 53) as f64;
+        (-2.0 * u.ln()).sqrt() * (std::f64::consts::TAU * v).cos()
+    }
+}
+
+/// `n` standard normal draws from the generator seeded with `seed`.
+pub fn normals(n: usize, seed: u64) -> Vec<f64> {
+    let mut rng = Lcg::new(seed);
+    (0..n).map(|_| rng.normal()).collect()
+}
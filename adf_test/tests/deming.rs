@@ -0,0 +1,51 @@
+//! Deming regression: symmetric in the legs, and OLS in the limit of a noise-free B.
+
+use adf_test::{deming_hedge, rolling_deming, rolling_ols};
+
+mod common;
+
+use common::Lcg;
+
+fn pair(n: usize) -> (Vec<f64>, Vec<f64>) {
+    let mut rng = Lcg::new(7);
+    let mut level = 40.0;
+    let mut prices_a = Vec::with_capacity(n);
+    let mut prices_b = Vec::with_capacity(n);
+    for _ in 0..n {
+        level += rng.centred();
+        prices_b.push(level + 0.3 * rng.centred());
+        prices_a.push(2.0 + 1.5 * level + 0.3 * rng.centred());
+    }
+    (prices_a, prices_b)
+}
+
+#[test]
+fn swapping_the_legs_inverts_the_hedge_ratio() {
+    let (prices_a, prices_b) = pair(300);
+    for ratio in [0.25, 1.0, 2.25] {
+        let forward = deming_hedge(&prices_a, &prices_b, Some(ratio)).unwrap();
+        let backward = deming_hedge(&prices_b, &prices_a, Some(1.0 / ratio)).unwrap();
+        assert!((forward.beta * backward.beta - 1.0).abs() < 1e-12, "ratio {}", ratio);
+        assert!((backward.alpha + forward.alpha / forward.beta).abs() < 1e-9);
+    }
+    // The true ratio here is Var(noise in A) / Var(noise in B) = 1
+    let orthogonal = deming_hedge(&prices_a, &prices_b, None).unwrap();
+    assert!((orthogonal.beta - 1.5).abs() < 0.05, "{}", orthogonal.beta);
+    assert!(deming_hedge(&prices_a, &prices_b, Some(0.0)).is_err());
+}
+
+#[test]
+fn rolling_deming_tends_to_rolling_ols() {
+    let (prices_a, prices_b) = pair(400);
+    let ols = rolling_ols(&prices_a, &prices_b, 60).unwrap().series();
+    let deming = rolling_deming(&prices_a, &prices_b, 60, Some(1e12)).unwrap();
+    for i in 0..prices_a.len() {
+        assert!((ols.betas()[i] - deming.betas()[i]).abs() < 1e-6, "bar {}", i);
+    }
+
+    let orthogonal = rolling_deming(&prices_a, &prices_b, 60, None).unwrap();
+    let last_window = deming_hedge(&prices_a[340..], &prices_b[340..], None).unwrap();
+    assert!((orthogonal.betas()[399] - last_window.beta).abs() < 1e-9);
+    assert!((orthogonal.alphas()[399] - last_window.alpha).abs() < 1e-7);
+    assert_eq!((orthogonal.alphas()[0], orthogonal.betas()[0]), (0.0, 1.0));
+}
@@ -0,0 +1,160 @@
+//! DF-GLS against a case worked by hand and arch's `DFGLS` with fixed lags, transcribed
+//! below: GLS detrending on quasi-differences with c-bar = -7 or -13.5, then a
+//! Dickey-Fuller regression without deterministic terms.
+
+use adf_test::mackinnon::{critical_values, mackinnonp};
+use adf_test::dfgls::gls_detrend;
+use adf_test::{dfgls_test, AdfOptions, LagCriterion, Regression};
+use nalgebra::{DMatrix, DVector};
+
+mod common;
+
+use common::normals;
+
+fn least_squares(x: &DMatrix<f64>, y: &DVector<f64>) -> (DVector<f64>, DMatrix<f64>) {
+    let xtx_inv = (x.transpose() * x).try_inverse().unwrap();
+    (&xtx_inv * x.transpose() * y, xtx_inv)
+}
+
+/// `DFGLS(y, lags, trend).stat`
+fn arch_dfgls(y: &[f64], trend: bool, lags: usize) -> f64 {
+    let nobs = y.len();
+    let (c, columns) = if trend { (-13.5, 2) } else { (-7.0, 1) };
+    let alpha = 1.0 + c / nobs as f64;
+    let z = DMatrix::from_fn(nobs, columns, |t, k| if k == 0 { 1.0 } else { (t + 1) as f64 });
+    let delta_z = DMatrix::from_fn(nobs, columns, |t, k| {
+        if t == 0 {
+            z[(t, k)]
+        } else {
+            z[(t, k)] - alpha * z[(t - 1, k)]
+        }
+    });
+    let delta_y = DVector::from_fn(nobs, |t, _| if t == 0 { y[0] } else { y[t] - alpha * y[t - 1] });
+    let (params, _) = least_squares(&delta_z, &delta_y);
+    let detrended = DVector::from_column_slice(y) - &z * params;
+
+    // ADF(detrended, lags, trend="n"): dy_t on y_{t-1}, dy_{t-1}, ..., dy_{t-lags}
+    let dy: Vec<f64> = detrended.as_slice().windows(2).map(|w| w[1] - w[0]).collect();
+    let rows = dy.len() - lags;
+    let x = DMatrix::from_fn(rows, 1 + lags, |i, k| {
+        let t = i + lags;
+        if k == 0 {
+            detrended[t]
+        } else {
+            dy[t - k]
+        }
+    });
+    let lhs = DVector::from_fn(rows, |i, _| dy[i + lags]);
+    let (beta, xtx_inv) = least_squares(&x, &lhs);
+    let resid = &lhs - &x * &beta;
+    let s2 = resid.dot(&resid) / (rows - x.ncols()) as f64;
+    beta[0] / (s2 * xtx_inv[(0, 0)]).sqrt()
+}
+
+fn series(n: usize) -> (Vec<f64>, Vec<f64>) {
+    let shocks = normals(n, 13);
+    let walk = shocks
+        .iter()
+        .scan(100.0, |level, e| {
+            *level += 0.1 + e;
+            Some(*level)
+        })
+        .collect();
+    let mut ar = 0.0;
+    let stationary = shocks
+        .iter()
+        .enumerate()
+        .map(|(i, e)| {
+            ar = 0.8 * ar + e;
+            5.0 + 0.02 * i as f64 + ar
+        })
+        .collect();
+    (walk, stationary)
+}
+
+#[test]
+fn matches_a_hand_worked_case() {
+    let y = [2.0, 1.0, 3.0, 2.0, 4.0, 3.0];
+    let options = AdfOptions::default()
+        .with_regression(Regression::Constant)
+        .with_autolag(LagCriterion::Fixed)
+        .with_max_lags(Some(0));
+    // alpha = 1 - 7 / 6 = -1/6, so the quasi-differenced constant is (1, 7/6, ..., 7/6)
+    // with z'z = 281 / 36 and z'y = 39 / 2: the GLS mean is 702 / 281, leaving the levels
+    // (-140, -421, 141, -140, 422, 141) / 281
+    let detrended = gls_detrend(&y, Regression::Constant).unwrap();
+    for (actual, expected) in detrended.iter().zip([-140.0, -421.0, 141.0, -140.0, 422.0, 141.0]) {
+        assert!((actual - expected / 281.0).abs() < 1e-12);
+    }
+
+    // Their differences on the lagged levels: gamma = -434145 / 414406 with
+    // ssr = 2171441 / 414406 on 4 degrees of freedom, so t^2 = 9548100 / 2171441
+    let result = dfgls_test(&y, &options).unwrap();
+    assert!((result.report().gamma + 434145.0 / 414406.0).abs() < 1e-12);
+    assert!((result.report().ssr - 2171441.0 / 414406.0).abs() < 1e-12);
+    let statistic = -(9548100.0_f64 / 2171441.0).sqrt();
+    assert!((result.test_statistic - statistic).abs() < 1e-12, "{}", result.test_statistic);
+    assert!((result.test_statistic + 2.0969325569).abs() < 1e-10);
+    assert_eq!(result.p_value, mackinnonp(result.test_statistic, Regression::NoConstant));
+}
+
+#[test]
+fn matches_arch_with_fixed_lags() {
+    let (walk, stationary) = series(250);
+    for (name, data) in [("walk", &walk), ("stationary", &stationary)] {
+        for (regression, trend) in [(Regression::Constant, false), (Regression::ConstantTrend, true)] {
+            for lags in [0, 2, 5] {
+                let options = AdfOptions::default()
+                    .with_regression(regression)
+                    .with_autolag(LagCriterion::Fixed)
+                    .with_max_lags(Some(lags as u32));
+                let result = dfgls_test(data, &options).unwrap();
+                let expected = arch_dfgls(data, trend, lags);
+                let case = format!("{} {} lags {}", name, regression, lags);
+                assert_eq!(result.optimal_lags as usize, lags, "{}", case);
+                assert!(
+                    (result.test_statistic - expected).abs() < 1e-9 * expected.abs().max(1.0),
+                    "{}: {} vs {}",
+                    case,
+                    result.test_statistic,
+                    expected
+                );
+                let nobs = result.report().nobs;
+                assert_eq!(nobs as usize, data.len() - 1 - lags, "{}", case);
+                if regression == Regression::Constant {
+                    // The no-constant Dickey-Fuller distribution
+                    assert_eq!(result.p_value, mackinnonp(result.test_statistic, Regression::NoConstant));
+                    assert_eq!(result.critical_value_table(), critical_values(Regression::NoConstant, nobs as usize));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn trend_case_uses_the_ers_table() {
+    let (walk, _) = series(101);
+    let options = AdfOptions::default()
+        .with_regression(Regression::ConstantTrend)
+        .with_autolag(LagCriterion::Fixed)
+        .with_max_lags(Some(0));
+    // 100 observations in the regression: ERS's T = 100 row
+    let cv = dfgls_test(&walk, &options).unwrap().critical_value_table();
+    assert!((cv.one_percent + 3.58).abs() < 1e-12);
+    assert!((cv.five_percent + 3.03).abs() < 1e-12);
+    assert!((cv.ten_percent + 2.74).abs() < 1e-12);
+
+    // 150 observations sit a third of the way from T = 200 to T = 100 in 1/T
+    let (longer, _) = series(151);
+    let cv = dfgls_test(&longer, &options).unwrap().critical_value_table();
+    assert!((cv.one_percent - (-3.58 - 2.0 * 3.46) / 3.0).abs() < 1e-12);
+    assert!((cv.five_percent - (-3.03 - 2.0 * 2.93) / 3.0).abs() < 1e-12);
+    assert!((cv.ten_percent - (-2.74 - 2.0 * 2.64) / 3.0).abs() < 1e-12);
+
+    // No p-value surface is published for the trend case; the decision uses the table
+    let result = dfgls_test(&walk, &options).unwrap();
+    assert!(result.p_value.is_nan());
+    assert_eq!(result.is_stationary, result.test_statistic < -3.03);
+
+    assert!(dfgls_test(&walk, &options.clone().with_regression(Regression::NoConstant)).is_err());
+}
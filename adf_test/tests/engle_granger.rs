@@ -0,0 +1,134 @@
+//! Engle-Granger against a pair worked by hand and statsmodels' `coint`, transcribed below
+//! with fixed lags: OLS of `y` on the trend terms and `x`, `adfuller(resid,
+//! regression="n")`, and MacKinnon's N = 2 surfaces.
+
+use adf_test::mackinnon::{cointegration_critical_values, cointegration_p_value};
+use adf_test::{engle_granger, AdfOptions, LagCriterion, Regression};
+use nalgebra::{DMatrix, DVector};
+
+mod common;
+
+use common::normals;
+
+fn least_squares(x: &DMatrix<f64>, y: &DVector<f64>) -> (DVector<f64>, DMatrix<f64>) {
+    let xtx_inv = (x.transpose() * x).try_inverse().unwrap();
+    (&xtx_inv * x.transpose() * y, xtx_inv)
+}
+
+/// `(tau, alpha, beta)` of `coint(y, x, trend, maxlag=lags, autolag=None)`.
+fn statsmodels_coint(y: &[f64], x: &[f64], n_trend: usize, lags: usize) -> (f64, f64, f64) {
+    let n = y.len();
+    // add_trend(x, trend, prepend=False): x, then const, t, t^2 with t counted from 1
+    let xx = DMatrix::from_fn(n, 1 + n_trend, |i, k| if k == 0 { x[i] } else { ((i + 1) as f64).powi(k as i32 - 1) });
+    let (params, _) = least_squares(&xx, &DVector::from_column_slice(y));
+    let resid = DVector::from_column_slice(y) - &xx * &params;
+
+    // adfuller(resid, maxlag=lags, autolag=None, regression="n")
+    let dy: Vec<f64> = resid.as_slice().windows(2).map(|w| w[1] - w[0]).collect();
+    let rows = dy.len() - lags;
+    let design = DMatrix::from_fn(rows, 1 + lags, |i, k| if k == 0 { resid[i + lags] } else { dy[i + lags - k] });
+    let lhs = DVector::from_fn(rows, |i, _| dy[i + lags]);
+    let (beta, xtx_inv) = least_squares(&design, &lhs);
+    let u = &lhs - &design * &beta;
+    let s2 = u.dot(&u) / (rows - design.ncols()) as f64;
+    let tau = beta[0] / (s2 * xtx_inv[(0, 0)]).sqrt();
+    (tau, if n_trend > 0 { params[1] } else { 0.0 }, params[0])
+}
+
+fn pair(n: usize, cointegrated: bool) -> (Vec<f64>, Vec<f64>) {
+    let trend = normals(n, 51);
+    let noise = normals(n, 52);
+    let own = normals(n, 53);
+    let (mut x, mut drift, mut spread) = (100.0, 0.0, 0.0);
+    let mut xs = Vec::with_capacity(n);
+    let mut ys = Vec::with_capacity(n);
+    for i in 0..n {
+        x += trend[i];
+        drift += own[i];
+        spread = 0.6 * spread + noise[i];
+        xs.push(x);
+        ys.push(if cointegrated { 5.0 + 1.5 * x + spread } else { 40.0 + drift + 0.5 * noise[i] });
+    }
+    (ys, xs)
+}
+
+#[test]
+fn matches_a_hand_worked_pair() {
+    let x = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0];
+    let y = [3.0, 6.0, 7.0, 9.0, 12.0, 13.0, 15.0];
+    let options = AdfOptions::default().with_autolag(LagCriterion::Fixed).with_max_lags(Some(0));
+    let result = engle_granger(&y, &x, &options).unwrap();
+    // Sxy / Sxx = 55 / 28 and 11 - 4 * 55 / 28 = 10 / 7
+    assert!((result.beta - 55.0 / 28.0).abs() < 1e-12 && (result.alpha - 10.0 / 7.0).abs() < 1e-12);
+    for (actual, expected) in result.residuals().iter().zip([-11.0, 18.0, -9.0, -8.0, 21.0, -6.0, -5.0]) {
+        assert!((actual - expected / 28.0).abs() < 1e-12);
+    }
+
+    // Residual differences (29, -27, 1, 29, -27, 1) / 28 on the lagged residuals:
+    // gamma = -1619 / 1067 and ssr = 1571 / 392 - 1619^2 / (1067 * 784) on 5 degrees of
+    // freedom, so tau^2 = 13105805 / 731353
+    let tau = -(13105805.0_f64 / 731353.0).sqrt();
+    assert!((result.test_statistic - tau).abs() < 1e-12, "{}", result.test_statistic);
+    assert_eq!((result.lags, result.nobs), (0, 6));
+    assert_eq!(result.p_value, cointegration_p_value(result.test_statistic, Regression::Constant, 2).unwrap());
+    // Seven bars leave the 5% critical value at -3.33613 - 6.1101 / 6 - 6.823 / 36 = -4.544
+    let five_percent = -3.33613 - 6.1101 / 6.0 - 6.823 / 36.0;
+    assert!((result.critical_value_table().five_percent - five_percent).abs() < 1e-12);
+    assert!(!result.is_cointegrated);
+}
+
+#[test]
+fn p_values_follow_the_asymptotic_critical_values() {
+    // MacKinnon (1994)'s p-value surface for two variables and MacKinnon (2010)'s
+    // asymptotic critical values are separate fits of the same distribution
+    for (regression, [one, five, ten]) in [
+        (Regression::Constant, [-3.89644, -3.33613, -3.04445]),
+        (Regression::ConstantTrend, [-4.32762, -3.78057, -3.49631]),
+    ] {
+        let cv = cointegration_critical_values(regression, 2, usize::MAX).unwrap();
+        assert!((cv.one_percent - one).abs() < 1e-5 && (cv.five_percent - five).abs() < 1e-5 && (cv.ten_percent - ten).abs() < 1e-5);
+        for (statistic, level) in [(one, 0.01), (five, 0.05), (ten, 0.10)] {
+            let p_value = cointegration_p_value(statistic, regression, 2).unwrap();
+            assert!((p_value - level).abs() < 0.1 * level, "{} at {}: {}", regression, statistic, p_value);
+        }
+    }
+}
+
+#[test]
+fn matches_statsmodels_coint_with_fixed_lags() {
+    for cointegrated in [true, false] {
+        let (y, x) = pair(250, cointegrated);
+        for regression in [Regression::Constant, Regression::ConstantTrend, Regression::ConstantTrendSquared] {
+            for lags in [0, 1, 4] {
+                let options = AdfOptions::default()
+                    .with_regression(regression)
+                    .with_autolag(LagCriterion::Fixed)
+                    .with_max_lags(Some(lags as u32));
+                let result = engle_granger(&y, &x, &options).unwrap();
+                let (tau, alpha, beta) = statsmodels_coint(&y, &x, regression.n_terms(), lags);
+                let case = format!("{} {} lags {}", cointegrated, regression, lags);
+                assert_eq!(result.lags as usize, lags, "{}", case);
+                assert_eq!(result.nobs as usize, 250 - 1 - lags, "{}", case);
+                assert!((result.test_statistic - tau).abs() < 1e-9 * tau.abs().max(1.0), "{}", case);
+                assert!((result.alpha - alpha).abs() < 1e-8 * alpha.abs().max(1.0), "{}", case);
+                assert!((result.beta - beta).abs() < 1e-9 * beta.abs().max(1.0), "{}", case);
+                // pval_asy = mackinnonp(tau, trend, N=2); crit = mackinnoncrit(N=2, trend, nobs - 1)
+                let p_value = cointegration_p_value(tau, regression, 2).unwrap();
+                assert!((result.p_value - p_value).abs() < 1e-9, "{}", case);
+                assert_eq!(result.critical_value_table(), cointegration_critical_values(regression, 2, 249).unwrap());
+                assert_eq!(result.is_cointegrated, cointegrated, "{}", case);
+            }
+        }
+    }
+}
+
+#[test]
+fn the_pairs_tables_need_deterministic_terms() {
+    let (y, x) = pair(100, true);
+    let options = AdfOptions::default().with_regression(Regression::NoConstant);
+    assert!(engle_granger(&y, &x, &options).is_err());
+    assert!(engle_granger(&y, &x[1..], &AdfOptions::default()).is_err());
+    // mackinnoncrit(N=2, "c", nobs=99)
+    let cv = engle_granger(&y, &x, &AdfOptions::default()).unwrap().critical_value_table();
+    assert!((cv.five_percent - (-3.33613 - 6.1101 / 99.0 - 6.823 / 99.0 / 99.0)).abs() < 1e-12);
+}
@@ -0,0 +1,108 @@
+//! Each `Error` variant is reachable from the public tests, with the reason in its message.
+
+use adf_test::error::Error;
+use adf_test::long_run_variance::Bandwidth;
+use adf_test::{adf_test, complete_adf_test, kpss_test, pp_test, AdfOptions, Regression};
+
+mod common;
+
+use common::normals;
+
+fn walk(n: usize) -> Vec<f64> {
+    normals(n, 41)
+        .iter()
+        .scan(10.0, |level, e| {
+            *level += e;
+            Some(*level)
+        })
+        .collect()
+}
+
+fn with(regression: Regression) -> AdfOptions {
+    AdfOptions::default().with_regression(regression)
+}
+
+#[test]
+fn short_series_need_more_data() {
+    let data = walk(5);
+    // Two observations per parameter of the lag-0 regression, and never fewer than 5
+    assert_eq!(
+        adf_test(&data, &with(Regression::Constant)).err(),
+        Some(Error::InsufficientData { required: 6, actual: 5 })
+    );
+    assert_eq!(
+        adf_test(&data, &with(Regression::ConstantTrend)).err(),
+        Some(Error::InsufficientData { required: 8, actual: 5 })
+    );
+    assert_eq!(
+        adf_test(&data[..4], &with(Regression::NoConstant)).err(),
+        Some(Error::InsufficientData { required: 5, actual: 4 })
+    );
+    assert!(adf_test(&data, &with(Regression::NoConstant)).is_ok());
+    assert!(matches!(pp_test(&data[..2], Regression::Constant, Bandwidth::Schwert), Err(Error::InsufficientData { .. })));
+
+    let message = Error::InsufficientData { required: 6, actual: 5 }.to_string();
+    assert_eq!(message, "insufficient data: 5 observations provided, at least 6 required");
+}
+
+#[test]
+fn non_finite_values_are_located() {
+    let mut data = walk(40);
+    data[17] = f64::NAN;
+    assert_eq!(adf_test(&data, &AdfOptions::default()).err(), Some(Error::NonFiniteInput { index: 17 }));
+    data[3] = f64::NEG_INFINITY;
+    assert_eq!(adf_test(&data, &AdfOptions::default()).err(), Some(Error::NonFiniteInput { index: 3 }));
+    // The length is checked first
+    assert!(matches!(adf_test(&data[..5], &AdfOptions::default()), Err(Error::InsufficientData { .. })));
+    assert_eq!(
+        Error::NonFiniteInput { index: 3 }.to_string(),
+        "non-finite input: value at index 3 is NaN or infinite"
+    );
+}
+
+#[test]
+fn constant_series_are_rejected() {
+    let flat = vec![101.25; 50];
+    assert_eq!(adf_test(&flat, &AdfOptions::default()).err(), Some(Error::ConstantSeries));
+    assert_eq!(kpss_test(&flat, Regression::Constant, Bandwidth::Auto).err(), Some(Error::ConstantSeries));
+    assert_eq!(Error::ConstantSeries.to_string(), "constant series: all observations are equal");
+}
+
+#[test]
+fn perfect_fits_are_singular() {
+    // Constant differences leave no residual variance to test against
+    let line: Vec<f64> = (0..50).map(|t| 3.0 + 0.5 * t as f64).collect();
+    for regression in [Regression::Constant, Regression::ConstantTrend] {
+        assert_eq!(adf_test(&line, &with(regression)).err(), Some(Error::SingularDesign), "{}", regression);
+    }
+    assert_eq!(
+        Error::SingularDesign.to_string(),
+        "singular design: the regression matrix is rank deficient"
+    );
+}
+
+#[test]
+fn unsupported_options_are_invalid() {
+    let data = walk(30);
+    // 30 bars with a constant allow at most 15 - 2 = 13 lags
+    let error = adf_test(&data, &AdfOptions::default().with_max_lags(Some(14))).err().unwrap();
+    assert_eq!(
+        error.to_string(),
+        "invalid option: max_lags 14 is too large for 30 observations (at most 13)"
+    );
+    assert!(adf_test(&data, &AdfOptions::default().with_max_lags(Some(13))).is_ok());
+    assert!(matches!("quadratic".parse::<Regression>(), Err(Error::InvalidOption(_))));
+}
+
+#[test]
+fn legacy_entry_point_reports_the_reason() {
+    let data = [1.0, 2.0, f64::NAN, 4.0, 5.0, 6.0, 7.0, 8.0];
+    assert_eq!(
+        complete_adf_test(&data, "ols", Regression::ConstantTrend).err(),
+        Some(Error::NonFiniteInput { index: 2 })
+    );
+    assert_eq!(
+        complete_adf_test(&data[3..], "kalman", Regression::ConstantTrend).err(),
+        Some(Error::InsufficientData { required: 8, actual: 5 })
+    );
+}
@@ -0,0 +1,216 @@
+//! The Johansen test on a pair small enough to work exactly, and against statsmodels'
+//! `coint_johansen`, transcribed below with its own numerics: detrending by
+//! `np.polyfit`-style residuals, the eigenproblem of `inv(skk) @ sig` solved as an ordinary
+//! (non-symmetric) one, and the vectors scaled by `inv(chol(du' skk du))`.
+
+use adf_test::{johansen_test, Regression};
+use nalgebra::{DMatrix, DVector};
+
+mod common;
+
+use common::normals;
+
+/// `detrend(y, order)`: residuals of every column on a polynomial in 0..rows, or `y` itself
+/// for order -1.
+fn sm_detrend(y: &DMatrix<f64>, order: i32) -> DMatrix<f64> {
+    if order < 0 {
+        return y.clone();
+    }
+    let rows = y.nrows();
+    let trend = DMatrix::from_fn(rows, order as usize + 1, |i, p| (i as f64).powi(p as i32));
+    sm_resid(y, &trend)
+}
+
+/// `resid(y, x) = y - x @ pinv(x) @ y`
+fn sm_resid(y: &DMatrix<f64>, x: &DMatrix<f64>) -> DMatrix<f64> {
+    let pinv = x.clone().pseudo_inverse(1e-15).unwrap();
+    y - x * (pinv * y)
+}
+
+struct Reference {
+    eigenvalues: Vec<f64>,
+    trace: Vec<f64>,
+    max_eigen: Vec<f64>,
+    eigenvectors: Vec<DVector<f64>>,
+}
+
+fn coint_johansen(endog: &DMatrix<f64>, det_order: i32, k_ar_diff: usize) -> Reference {
+    let f = if det_order > -1 { 0 } else { det_order };
+    let x = sm_detrend(endog, det_order);
+    let (n, k) = x.shape();
+    let dx = DMatrix::from_fn(n - 1, k, |i, j| x[(i + 1, j)] - x[(i, j)]);
+    // lagmat(dx, k_ar_diff)[k_ar_diff:]: lag 1 of every column, then lag 2, ...
+    let rows = n - 1 - k_ar_diff;
+    let z = DMatrix::from_fn(rows, k * k_ar_diff, |i, c| dx[(i + k_ar_diff - 1 - c / k, c % k)]);
+    let z = sm_detrend(&z, f);
+    let dx = sm_detrend(&dx.rows(k_ar_diff, rows).into_owned(), f);
+    let r0t = sm_resid(&dx, &z);
+    // lx = x[:-k_ar_diff][1:]
+    let lx = sm_detrend(&x.rows(1, n - k_ar_diff - 1).into_owned(), f);
+    let rkt = sm_resid(&lx, &z);
+
+    let t = rkt.nrows() as f64;
+    let skk = rkt.transpose() * &rkt / t;
+    let sk0 = rkt.transpose() * &r0t / t;
+    let s00 = r0t.transpose() * &r0t / t;
+    let sig = &sk0 * s00.try_inverse().unwrap() * sk0.transpose();
+    let m = skk.clone().try_inverse().unwrap() * sig;
+
+    // np.linalg.eig: the eigenvalues are real here, and each vector spans the null space
+    // of m - au * I
+    let au: Vec<f64> = m.complex_eigenvalues().iter().map(|c| c.re).collect();
+    let du = DMatrix::from_columns(
+        &au.iter()
+            .map(|&lambda| {
+                let svd = (&m - DMatrix::identity(k, k) * lambda).svd(false, true);
+                let v_t = svd.v_t.unwrap();
+                let smallest = svd.singular_values.imin();
+                v_t.row(smallest).transpose()
+            })
+            .collect::<Vec<_>>(),
+    );
+    let chol = (du.transpose() * &skk * &du).cholesky().unwrap().l();
+    let dt = &du * chol.try_inverse().unwrap();
+
+    let mut order: Vec<usize> = (0..k).collect();
+    order.sort_by(|&a, &b| au[b].total_cmp(&au[a]));
+    let eigenvalues: Vec<f64> = order.iter().map(|&i| au[i]).collect();
+    let log_terms: Vec<f64> = eigenvalues.iter().map(|a| (1.0 - a).ln()).collect();
+    Reference {
+        trace: (0..k).map(|r| -t * log_terms[r..].iter().sum::<f64>()).collect(),
+        max_eigen: log_terms.iter().map(|l| -t * l).collect(),
+        eigenvectors: order.iter().map(|&i| dt.column(i).into_owned()).collect(),
+        eigenvalues,
+    }
+}
+
+/// Three assets: the first two share a stochastic trend, the third wanders on its own.
+fn basket(n: usize) -> Vec<Vec<f64>> {
+    let trend = normals(n, 31);
+    let noise_a = normals(n, 32);
+    let noise_b = normals(n, 33);
+    let own = normals(n, 34);
+    let (mut w, mut v) = (0.0, 0.0);
+    let mut series = vec![Vec::new(); 3];
+    for i in 0..n {
+        w += trend[i];
+        v += own[i];
+        series[0].push(50.0 + w + 0.8 * noise_a[i] + 0.01 * i as f64);
+        series[1].push(30.0 + 0.5 * w + 0.6 * noise_b[i]);
+        series[2].push(40.0 + v);
+    }
+    series
+}
+
+#[test]
+fn matches_exact_moments_of_a_small_pair() {
+    // Without deterministic terms or lags, the eigenvalues solve |lambda S_kk - S_k0 S_00^-1 S_0k| = 0
+    // on the raw moments of the levels and differences:
+    //   S_kk = [100 116; 116 137], S_k0 = [4 14; 8 17], S_00 = [16 11; 11 11]
+    // S_kk^-1 S_k0 S_00^-1 S_0k then has trace 640 / 671 and determinant 44 / 305
+    let series = vec![
+        vec![1.0, 3.0, 2.0, 4.0, 3.0, 5.0, 6.0, 5.0],
+        vec![2.0, 3.0, 3.0, 5.0, 4.0, 5.0, 7.0, 7.0],
+    ];
+    let result = johansen_test(&series, Regression::NoConstant, 0).unwrap();
+    let eigenvalues = result.eigenvalues();
+    assert!((eigenvalues[0] + eigenvalues[1] - 640.0 / 671.0).abs() < 1e-12);
+    assert!((eigenvalues[0] * eigenvalues[1] - 44.0 / 305.0).abs() < 1e-12);
+    assert!((eigenvalues[0] - 0.7652947694).abs() < 1e-10, "{:?}", eigenvalues);
+
+    // Seven rows: the rank-0 trace statistic is -7 ln((1 - l1)(1 - l2)) = -7 ln(1 - tr + det)
+    assert_eq!(result.nobs, 7);
+    let trace = -7.0 * (1.0 - 640.0 / 671.0 + 44.0 / 305.0_f64).ln();
+    assert!((result.trace_statistics()[0] - trace).abs() < 1e-10, "{}", result.trace_statistics()[0]);
+    assert!((result.max_eigen_statistics()[0] - 10.1459742307).abs() < 1e-9);
+    assert!((result.trace_statistics()[1] - 1.4621439345).abs() < 1e-9);
+
+    // The first eigenvector, normalised on the first series
+    let weights = result.hedge_weights(0).unwrap();
+    assert_eq!(weights[0], 1.0);
+    assert!((weights[1] + 0.7878104390).abs() < 1e-9, "{:?}", weights);
+}
+
+#[test]
+fn matches_statsmodels_coint_johansen() {
+    let series = basket(400);
+    for (regression, det_order) in [
+        (Regression::NoConstant, -1),
+        (Regression::Constant, 0),
+        (Regression::ConstantTrend, 1),
+    ] {
+        for lags in [1, 3] {
+            for k in [2, 3] {
+                let series = &series[..k];
+                let endog = DMatrix::from_fn(series[0].len(), k, |i, j| series[j][i]);
+                let expected = coint_johansen(&endog, det_order, lags);
+                let result = johansen_test(series, regression, lags).unwrap();
+                let case = format!("{} lags {} k {}", regression, lags, k);
+                assert_eq!(result.nobs as usize, series[0].len() - 1 - lags, "{}", case);
+
+                for r in 0..k {
+                    assert!((result.eigenvalues()[r] - expected.eigenvalues[r]).abs() < 1e-9, "{}", case);
+                    let trace = expected.trace[r];
+                    assert!((result.trace_statistics()[r] - trace).abs() < 1e-7 * trace.max(1.0), "{}", case);
+                    let max_eigen = expected.max_eigen[r];
+                    assert!(
+                        (result.max_eigen_statistics()[r] - max_eigen).abs() < 1e-7 * max_eigen.max(1.0),
+                        "{}",
+                        case
+                    );
+
+                    let v = &expected.eigenvectors[r];
+                    let weights = result.hedge_weights(r).unwrap();
+                    for (w, e) in weights.iter().zip(v.iter()) {
+                        let normalized = e / v[0];
+                        assert!((w - normalized).abs() < 1e-6 * normalized.abs().max(1.0), "{}", case);
+                    }
+                }
+
+                // Sequential trace test at 5% on the reference statistics
+                let cv = result.trace_critical_value_table();
+                let rank = (0..k).position(|r| expected.trace[r] < cv[r].five_percent).unwrap_or(k);
+                assert_eq!(result.rank as usize, rank, "{}", case);
+            }
+        }
+    }
+}
+
+#[test]
+fn the_shared_trend_gives_rank_one_with_the_simulated_weights() {
+    let series = basket(400);
+    let result = johansen_test(&series, Regression::Constant, 1).unwrap();
+    assert_eq!(result.rank, 1);
+    assert_eq!(result.max_eigen_rank, 1);
+    // statsmodels' c_sjt for three and two common trends under a constant
+    assert_eq!(result.trace_critical_value_table()[0].five_percent, 29.7961);
+    assert_eq!(result.trace_critical_value_table()[1].five_percent, 15.4943);
+    let weights = result.hedge_weights(0).unwrap();
+    assert_eq!(weights[0], 1.0);
+    // The spread is a - 2 b, with the third asset left out
+    assert!((weights[1] + 2.0).abs() < 0.25, "{:?}", weights);
+    assert!(weights[2].abs() < 0.25, "{:?}", weights);
+}
+
+#[test]
+fn invariant_to_price_units_and_levels() {
+    // Quoting the basket around 50,000 in units a thousand times larger changes nothing
+    // once the deterministic terms are removed, although the moment matrices of the raw
+    // levels would lose most of their digits
+    let series = basket(400);
+    let prices: Vec<Vec<f64>> = series
+        .iter()
+        .map(|s| s.iter().map(|v| 50_000.0 + 1_000.0 * v).collect())
+        .collect();
+    for regression in [Regression::Constant, Regression::ConstantTrend] {
+        let expected = johansen_test(&series, regression, 2).unwrap();
+        let result = johansen_test(&prices, regression, 2).unwrap();
+        for r in 0..3 {
+            assert!((result.eigenvalues()[r] - expected.eigenvalues()[r]).abs() < 1e-10, "{}", regression);
+            let (weights, reference) = (result.hedge_weights(r).unwrap(), expected.hedge_weights(r).unwrap());
+            for (w, e) in weights.iter().zip(&reference) {
+                assert!((w - e).abs() < 1e-8 * e.abs().max(1.0), "{}: {:?} vs {:?}", regression, weights, reference);
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! The Kalman hedge on a simulated pair whose coefficients really are random walks.
+
+use adf_test::{Error, KalmanHedge};
+
+mod common;
+
+use common::normals;
+
+const PROCESS_NOISE: f64 = 1e-6;
+const MEASUREMENT_NOISE: f64 = 0.25;
+
+/// Straight transcription of `kalmanFilter` from the calculations worker, which ignores its
+/// `measurementNoise` argument: `(alphas, hedgeRatios)`.
+fn worker_kalman_filter(prices_a: &[f64], prices_b: &[f64], process_noise: f64, initial_lookback: usize) -> (Vec<f64>, Vec<f64>) {
+    let scalar_inverse = |s: f64| if s.abs() < 1e-10 { 1.0 } else { 1.0 / s };
+    let lookback = initial_lookback as f64;
+    let (mut sum_a, mut sum_b, mut sum_ab, mut sum_b2) = (0.0, 0.0, 0.0, 0.0);
+    for i in 0..initial_lookback {
+        sum_a += prices_a[i];
+        sum_b += prices_b[i];
+        sum_ab += prices_a[i] * prices_b[i];
+        sum_b2 += prices_b[i] * prices_b[i];
+    }
+    let (mean_a, mean_b) = (sum_a / lookback, sum_b / lookback);
+    let numerator = lookback * sum_ab - sum_a * sum_b;
+    let denominator = lookback * sum_b2 - sum_b * sum_b;
+    let initial_beta = if denominator.abs() > 1e-10 { numerator / denominator } else { 1.0 };
+    let initial_alpha = mean_a - initial_beta * mean_b;
+    let mut residual_sum_squares = 0.0;
+    for i in 0..initial_lookback {
+        let residual = prices_a[i] - (initial_alpha + initial_beta * prices_b[i]);
+        residual_sum_squares += residual * residual;
+    }
+    let adaptive_r = residual_sum_squares / (lookback - 2.0);
+
+    let mut x = [initial_alpha, initial_beta];
+    let mut p = [[1000.0, 0.0], [0.0, 1000.0]];
+    let mut alphas = vec![initial_alpha; initial_lookback];
+    let mut hedge_ratios = vec![initial_beta; initial_lookback];
+    for i in initial_lookback..prices_a.len() {
+        let p_pred = [[p[0][0] + process_noise, p[0][1]], [p[1][0], p[1][1] + process_noise]];
+        let h = [1.0, prices_b[i]];
+        let innovation = prices_a[i] - (h[0] * x[0] + h[1] * x[1]);
+        let p_h = [p_pred[0][0] * h[0] + p_pred[0][1] * h[1], p_pred[1][0] * h[0] + p_pred[1][1] * h[1]];
+        let innovation_covariance = p_h[0] * h[0] + p_h[1] * h[1] + adaptive_r;
+        let k = [p_h[0] * scalar_inverse(innovation_covariance), p_h[1] * scalar_inverse(innovation_covariance)];
+        x = [x[0] + k[0] * innovation, x[1] + k[1] * innovation];
+        let i_kh = [[1.0 - k[0] * h[0], -k[0] * h[1]], [-k[1] * h[0], 1.0 - k[1] * h[1]]];
+        p = [
+            [
+                i_kh[0][0] * p_pred[0][0] + i_kh[0][1] * p_pred[1][0],
+                i_kh[0][0] * p_pred[0][1] + i_kh[0][1] * p_pred[1][1],
+            ],
+            [
+                i_kh[1][0] * p_pred[0][0] + i_kh[1][1] * p_pred[1][0],
+                i_kh[1][0] * p_pred[0][1] + i_kh[1][1] * p_pred[1][1],
+            ],
+        ];
+        alphas.push(x[0]);
+        hedge_ratios.push(x[1]);
+    }
+    (alphas, hedge_ratios)
+}
+
+fn simulated_pair(n: usize) -> (Vec<f64>, Vec<f64>) {
+    let shocks = normals(4 * n, 2024);
+    let (mut alpha, mut beta, mut b) = (5.0, 1.2, 100.0);
+    let mut prices_a = Vec::with_capacity(n);
+    let mut prices_b = Vec::with_capacity(n);
+    for t in 0..n {
+        b += shocks[4 * t];
+        alpha += PROCESS_NOISE.sqrt() * shocks[4 * t + 1];
+        beta += PROCESS_NOISE.sqrt() * shocks[4 * t + 2];
+        prices_a.push(alpha + beta * b + MEASUREMENT_NOISE.sqrt() * shocks[4 * t + 3]);
+        prices_b.push(b);
+    }
+    (prices_a, prices_b)
+}
+
+#[test]
+fn smoother_ends_on_the_filtered_state() {
+    let (prices_a, prices_b) = simulated_pair(600);
+    let hedge = KalmanHedge::new(1e-4, None, 60);
+    let filtered = hedge.filter(&prices_a, &prices_b).unwrap().series();
+    let smoothed = hedge.smooth(&prices_a, &prices_b).unwrap();
+    let last = prices_a.len() - 1;
+    assert!((filtered.betas()[last] - smoothed.series().betas()[last]).abs() < 1e-9);
+    assert!((filtered.alphas()[last] - smoothed.series().alphas()[last]).abs() < 1e-9);
+    assert_eq!(filtered.betas()[..60], smoothed.series().betas()[..60]);
+    assert!(smoothed.beta_std_errors()[59].is_nan());
+    assert!(smoothed.beta_std_errors()[300] > 0.0);
+}
+
+#[test]
+fn em_recovers_the_simulated_noise() {
+    let (prices_a, prices_b) = simulated_pair(800);
+    let start = KalmanHedge::new(1e-4, None, 60);
+    let estimate = start.estimate_noise(&prices_a, &prices_b, 2000).unwrap();
+    assert!(estimate.converged);
+    assert!(estimate.log_likelihood > start.filter(&prices_a, &prices_b).unwrap().log_likelihood);
+    assert!((estimate.measurement_noise / MEASUREMENT_NOISE - 1.0).abs() < 0.25, "{:?}", estimate);
+    assert!(estimate.process_noise > PROCESS_NOISE / 4.0 && estimate.process_noise < PROCESS_NOISE * 4.0, "{:?}", estimate);
+
+    let refit = estimate.hedge().filter(&prices_a, &prices_b).unwrap();
+    assert!((refit.log_likelihood - estimate.log_likelihood).abs() < 1e-9);
+}
+
+#[test]
+fn adaptive_filter_matches_the_worker() {
+    let (prices_a, prices_b) = simulated_pair(500);
+    for (process_noise, lookback) in [(1e-4, 60), (1e-6, 30), (0.0, 3)] {
+        let (alphas, betas) = worker_kalman_filter(&prices_a, &prices_b, process_noise, lookback);
+        let result = KalmanHedge::new(process_noise, None, lookback).filter(&prices_a, &prices_b).unwrap();
+        let series = result.series();
+        for i in 0..prices_a.len() {
+            assert!((series.betas()[i] - betas[i]).abs() < 1e-9 * betas[i].abs().max(1.0), "bar {}", i);
+            assert!((series.alphas()[i] - alphas[i]).abs() < 1e-9 * alphas[i].abs().max(1.0), "bar {}", i);
+            let spread = prices_a[i] - (alphas[i] + betas[i] * prices_b[i]);
+            assert!((series.spreads()[i] - spread).abs() < 1e-7, "bar {}", i);
+        }
+    }
+}
+
+#[test]
+fn nothing_to_filter_after_the_warm_up_is_an_error() {
+    let (prices_a, prices_b) = simulated_pair(60);
+    let hedge = KalmanHedge::new(1e-4, None, 60);
+    let expected = Error::InsufficientData { required: 61, actual: 60 };
+    assert_eq!(hedge.filter(&prices_a, &prices_b).err(), Some(expected.clone()));
+    assert_eq!(hedge.estimate_noise(&prices_a, &prices_b, 100).err(), Some(expected));
+    assert!(KalmanHedge::new(1e-4, None, 59).filter(&prices_a, &prices_b).is_ok());
+}
@@ -0,0 +1,168 @@
+//! KPSS against statsmodels' `kpss`: level and trend cases worked by hand and a transcription
+//! of the statsmodels routine on a longer series.
+
+use adf_test::long_run_variance::Bandwidth;
+use adf_test::{kpss_test, Regression};
+
+mod common;
+
+use common::normals;
+
+/// `np.interp(stat, crit, pvals)` over statsmodels' table, clipped at its ends.
+fn statsmodels_p_value(statistic: f64, crit: [f64; 4]) -> f64 {
+    let pvals = [0.10, 0.05, 0.025, 0.01];
+    if statistic <= crit[0] {
+        return pvals[0];
+    }
+    if statistic >= crit[3] {
+        return pvals[3];
+    }
+    let k = (0..3).find(|&k| statistic <= crit[k + 1]).unwrap();
+    pvals[k] + (statistic - crit[k]) * (pvals[k + 1] - pvals[k]) / (crit[k + 1] - crit[k])
+}
+
+/// statsmodels' `kpss(x, regression, nlags)`: `(statistic, lags, p-value)`, where `nlags`
+/// of `None` is "auto".
+fn statsmodels_kpss(x: &[f64], trend: bool, nlags: Option<usize>) -> (f64, usize, f64) {
+    let nobs = x.len();
+    let n = nobs as f64;
+    let resids: Vec<f64> = if trend {
+        // OLS on [1, t] through the normal equations with centred time
+        let t_mean = (n - 1.0) / 2.0;
+        let x_mean = x.iter().sum::<f64>() / n;
+        let s_tt: f64 = (0..nobs).map(|t| (t as f64 - t_mean).powi(2)).sum();
+        let s_tx: f64 = x.iter().enumerate().map(|(t, v)| (t as f64 - t_mean) * (v - x_mean)).sum();
+        let slope = s_tx / s_tt;
+        x.iter().enumerate().map(|(t, v)| v - x_mean - slope * (t as f64 - t_mean)).collect()
+    } else {
+        let mean = x.iter().sum::<f64>() / n;
+        x.iter().map(|v| v - mean).collect()
+    };
+    let dot = |i: usize| -> f64 { (i..nobs).map(|t| resids[t] * resids[t - i]).sum() };
+
+    let nlags = nlags.unwrap_or_else(|| {
+        // _kpss_autolag
+        let covlags = n.powf(2.0 / 9.0) as usize;
+        let mut s0 = dot(0) / n;
+        let mut s1 = 0.0;
+        for i in 1..=covlags {
+            let resids_prod = dot(i) / (n / 2.0);
+            s0 += resids_prod;
+            s1 += i as f64 * resids_prod;
+        }
+        let s_hat = s1 / s0;
+        let gamma_hat = 1.1447 * (s_hat * s_hat).powf(1.0 / 3.0);
+        (gamma_hat * n.powf(1.0 / 3.0)) as usize
+    });
+    let nlags = nlags.min(nobs - 1);
+
+    let mut cumsum = 0.0;
+    let eta = resids
+        .iter()
+        .map(|e| {
+            cumsum += e;
+            cumsum * cumsum
+        })
+        .sum::<f64>()
+        / (n * n);
+    // _sigma_est_kpss
+    let mut s_hat = dot(0);
+    for i in 1..=nlags {
+        s_hat += 2.0 * dot(i) * (1.0 - i as f64 / (nlags as f64 + 1.0));
+    }
+    let kpss_stat = eta / (s_hat / n);
+    let crit = if trend {
+        [0.119, 0.146, 0.176, 0.216]
+    } else {
+        [0.347, 0.463, 0.574, 0.739]
+    };
+    (kpss_stat, nlags, statsmodels_p_value(kpss_stat, crit))
+}
+
+#[test]
+fn matches_a_hand_worked_level_case() {
+    // Residuals -2..2, partial sums -2, -3, -3, -2, 0: eta = 26 / 25
+    let x = [1.0, 2.0, 3.0, 4.0, 5.0];
+    let no_lags = kpss_test(&x, Regression::Constant, Bandwidth::Fixed(0)).unwrap();
+    assert!((no_lags.test_statistic - 0.52).abs() < 1e-12);
+    assert!((no_lags.p_value - (0.05 - 0.057 / 0.111 * 0.025)).abs() < 1e-12);
+    assert!(!no_lags.is_stationary);
+
+    // One lag adds 2 * 4 * (1 - 1/2) to the sum of squares of 10
+    let one_lag = kpss_test(&x, Regression::Constant, Bandwidth::Fixed(1)).unwrap();
+    assert!((one_lag.test_statistic - 1.04 / 2.8).abs() < 1e-12);
+    assert!((one_lag.p_value - (0.10 - (1.04 / 2.8 - 0.347) / 0.116 * 0.05)).abs() < 1e-12);
+    assert!(one_lag.is_stationary);
+
+    // The Hobijn bandwidth picks that one lag: int(0.667 * 5^(1/3)) = 1
+    let auto = kpss_test(&x, Regression::Constant, Bandwidth::Auto).unwrap();
+    assert_eq!(auto.lags, 1);
+    assert_eq!(auto.test_statistic, one_lag.test_statistic);
+
+    let cv = auto.critical_value_table();
+    assert_eq!((cv.one_percent, cv.five_percent, cv.ten_percent), (0.739, 0.463, 0.347));
+    let trend = [1.0, 3.0, 2.0, 4.0, 6.0, 5.0];
+    let cv = kpss_test(&trend, Regression::ConstantTrend, Bandwidth::Auto).unwrap().critical_value_table();
+    assert_eq!((cv.one_percent, cv.five_percent, cv.ten_percent), (0.216, 0.146, 0.119));
+}
+
+#[test]
+fn matches_a_hand_worked_trend_case() {
+    // Time 0..5 has mean 2.5 and the series mean 3.5; the slope is 15.5 / 17.5 = 31 / 35,
+    // leaving residuals of (-20, 58, -74, 4, 82, -50) / 70
+    let x = [1.0, 3.0, 2.0, 4.0, 6.0, 5.0];
+    // Partial sums (-20, 38, -36, -32, 50, 0) / 70 give eta = 6664 / (4900 * 36), and the
+    // residual sum of squares is 18480 / 4900: the statistic is 6664 / (6 * 18480)
+    let no_lags = kpss_test(&x, Regression::ConstantTrend, Bandwidth::Fixed(0)).unwrap();
+    assert!((no_lags.test_statistic - 119.0 / 1980.0).abs() < 1e-12, "{}", no_lags.test_statistic);
+    // Below the 10% critical value of 0.119, where the table ends
+    assert_eq!(no_lags.p_value, 0.10);
+    assert!(no_lags.is_stationary);
+
+    // The first autocovariance, -9520 / 4900, enters with weight 2 * (1 - 1/2)
+    let one_lag = kpss_test(&x, Regression::ConstantTrend, Bandwidth::Fixed(1)).unwrap();
+    assert!((one_lag.test_statistic - 119.0 / 960.0).abs() < 1e-12, "{}", one_lag.test_statistic);
+    assert!((one_lag.p_value - (0.10 - (119.0 / 960.0 - 0.119) / 0.027 * 0.05)).abs() < 1e-12);
+    assert!(one_lag.is_stationary);
+}
+
+#[test]
+fn matches_statsmodels_on_stationary_and_trending_series() {
+    let shocks = normals(250, 17);
+    let mut ar = 0.0;
+    let stationary: Vec<f64> = shocks
+        .iter()
+        .map(|e| {
+            ar = 0.6 * ar + e;
+            10.0 + ar
+        })
+        .collect();
+    let walk: Vec<f64> = shocks
+        .iter()
+        .scan(0.0, |level, e| {
+            *level += e;
+            Some(*level + 0.05)
+        })
+        .collect();
+
+    for (name, series) in [("stationary", &stationary), ("walk", &walk)] {
+        for (regression, trend) in [(Regression::Constant, false), (Regression::ConstantTrend, true)] {
+            for (bandwidth, nlags) in [
+                (Bandwidth::Auto, None),
+                // "legacy": ceil(12 * 2.5^(1/4)) = 16
+                (Bandwidth::Schwert, Some(16)),
+                (Bandwidth::Fixed(4), Some(4)),
+            ] {
+                let (statistic, lags, p_value) = statsmodels_kpss(series, trend, nlags);
+                let result = kpss_test(series, regression, bandwidth).unwrap();
+                let case = format!("{} {} {:?}", name, regression, bandwidth);
+                assert_eq!(result.lags as usize, lags, "{}", case);
+                assert!((result.test_statistic - statistic).abs() < 1e-10 * statistic, "{}", case);
+                assert!((result.p_value - p_value).abs() < 1e-12, "{}", case);
+            }
+        }
+    }
+    // The walk rejects stationarity around a level; the AR(1) does not
+    assert!(!kpss_test(&walk, Regression::Constant, Bandwidth::Auto).unwrap().is_stationary);
+    assert!(kpss_test(&stationary, Regression::Constant, Bandwidth::Auto).unwrap().is_stationary);
+}
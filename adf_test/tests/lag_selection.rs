@@ -0,0 +1,227 @@
+//! The autolag criteria against a transcription of statsmodels' `adfuller` lag search, and
+//! the selection rules on hand-made candidates.
+
+use adf_test::error::Error;
+use adf_test::lag_selection::{schwert_max_lag, select_lag, LagCandidate};
+use adf_test::{adf_test, complete_adf_test, AdfOptions, LagCriterion, Regression};
+use nalgebra::{DMatrix, DVector};
+
+mod common;
+
+use common::normals;
+
+struct Fit {
+    params: Vec<f64>,
+    t_values: Vec<f64>,
+    ssr: f64,
+    nobs: usize,
+}
+
+/// The `adfuller` regression with `lags` lagged differences on the differences from
+/// `first` onwards, through the normal equations.
+fn adfuller_regression(y: &[f64], n_trend: usize, lags: usize, first: usize) -> Fit {
+    let dy: Vec<f64> = y.windows(2).map(|w| w[1] - w[0]).collect();
+    let nobs = dy.len() - first;
+    let x = DMatrix::from_fn(nobs, n_trend + 1 + lags, |i, k| {
+        let t = i + first;
+        match k {
+            _ if k < n_trend => ((i + 1) as f64).powi(k as i32),
+            _ if k == n_trend => y[t],
+            _ => dy[t - (k - n_trend)],
+        }
+    });
+    let lhs = DVector::from_fn(nobs, |i, _| dy[i + first]);
+    let xtx_inv = (x.transpose() * &x).try_inverse().unwrap();
+    let params = &xtx_inv * x.transpose() * &lhs;
+    let resid = &lhs - &x * &params;
+    let ssr = resid.dot(&resid);
+    let s2 = ssr / (nobs - x.ncols()) as f64;
+    let t_values = (0..x.ncols()).map(|k| params[k] / (s2 * xtx_inv[(k, k)]).sqrt()).collect();
+    Fit {
+        params: params.iter().copied().collect(),
+        t_values,
+        ssr,
+        nobs,
+    }
+}
+
+/// `(usedlag, adfstat)` of `adfuller(y, maxlag, regression, autolag)`: every lag fitted on
+/// the maxlag-trimmed sample, the information criteria from the Gaussian log-likelihood,
+/// then the chosen lag re-estimated on its own sample.
+fn adfuller_autolag(y: &[f64], n_trend: usize, maxlag: usize, autolag: LagCriterion) -> (usize, f64) {
+    let fits: Vec<Fit> = (0..=maxlag).map(|lags| adfuller_regression(y, n_trend, lags, maxlag)).collect();
+    let ic = |fit: &Fit| {
+        let n = fit.nobs as f64;
+        let k = fit.params.len() as f64;
+        let llf = -n / 2.0 * ((2.0 * std::f64::consts::PI).ln() + (fit.ssr / n).ln() + 1.0);
+        match autolag {
+            LagCriterion::Aic => -2.0 * llf + 2.0 * k,
+            LagCriterion::Bic => -2.0 * llf + n.ln() * k,
+            LagCriterion::Hqic => -2.0 * llf + 2.0 * n.ln().ln() * k,
+            _ => unreachable!(),
+        }
+    };
+    let lags = match autolag {
+        LagCriterion::TStat => (0..=maxlag)
+            .rev()
+            .find(|&lags| fits[lags].t_values.last().unwrap().abs() >= 1.6448536269514722)
+            .unwrap_or(0),
+        _ => (0..=maxlag).min_by(|&a, &b| ic(&fits[a]).total_cmp(&ic(&fits[b]))).unwrap(),
+    };
+    let fit = adfuller_regression(y, n_trend, lags, lags);
+    (lags, fit.t_values[n_trend])
+}
+
+/// A unit root whose differences follow an AR(2), so that the criteria have lags to find.
+fn ar2_differences(n: usize, seed: u64) -> Vec<f64> {
+    let (mut d1, mut d2) = (0.0, 0.0);
+    normals(n, seed)
+        .iter()
+        .scan(50.0, |level, e| {
+            let d = 0.45 * d1 - 0.3 * d2 + e;
+            d2 = d1;
+            d1 = d;
+            *level += d;
+            Some(*level)
+        })
+        .collect()
+}
+
+#[test]
+fn criteria_match_adfuller_autolag() {
+    for seed in [5, 6, 7] {
+        let data = ar2_differences(200, seed);
+        for regression in [Regression::Constant, Regression::ConstantTrend] {
+            for autolag in [LagCriterion::Aic, LagCriterion::Bic, LagCriterion::Hqic, LagCriterion::TStat] {
+                for max_lags in [None, Some(6)] {
+                    // 200 bars: Schwert's ceil(12 * 2^(1/4)) = 15
+                    let maxlag = max_lags.unwrap_or(15) as usize;
+                    let options = AdfOptions::default()
+                        .with_regression(regression)
+                        .with_autolag(autolag)
+                        .with_max_lags(max_lags);
+                    let result = adf_test(&data, &options).unwrap();
+                    let (lags, statistic) = adfuller_autolag(&data, regression.n_terms(), maxlag, autolag);
+                    let case = format!("seed {} {} {} {:?}", seed, regression, autolag, max_lags);
+                    assert_eq!(result.optimal_lags as usize, lags, "{}", case);
+                    assert!(
+                        (result.test_statistic - statistic).abs() < 1e-8 * statistic.abs().max(1.0),
+                        "{}: {} vs {}",
+                        case,
+                        result.test_statistic,
+                        statistic
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn fixed_uses_the_maximum_lag() {
+    let data = ar2_differences(254, 8);
+    let fixed = AdfOptions::default().with_autolag(LagCriterion::Fixed);
+    // Schwert's rule: ceil(12 * 2.54^(1/4)) = 16
+    assert_eq!(schwert_max_lag(254), 16);
+    assert_eq!(schwert_max_lag(100), 12);
+    assert_eq!(adf_test(&data, &fixed).unwrap().optimal_lags, 16);
+    assert_eq!(adf_test(&data, &fixed.clone().with_max_lags(Some(4))).unwrap().optimal_lags, 4);
+
+    // Capped at n / 2 - ntrend - 1 on short series: 20 bars with a constant allow 8
+    assert_eq!(schwert_max_lag(20), 9);
+    assert_eq!(adf_test(&data[..20], &fixed).unwrap().optimal_lags, 8);
+    assert!(matches!(
+        adf_test(&data[..20], &fixed.clone().with_max_lags(Some(9))),
+        Err(Error::InvalidOption(_))
+    ));
+}
+
+#[test]
+fn selection_rules_on_given_candidates() {
+    let candidate = |lags: usize, ssr: f64, last_lag_t: f64| LagCandidate {
+        lags,
+        ssr,
+        nobs: 100,
+        n_params: 2 + lags,
+        last_lag_t,
+    };
+    // Against a likelihood gain of 100 ln(0.96) = -4.08, the extra parameter costs 2 under
+    // AIC, 2 ln(ln(100)) = 3.05 under HQIC and ln(100) = 4.61 under BIC
+    let candidates = [candidate(0, 100.0, 0.0), candidate(1, 96.0, 2.5)];
+    assert_eq!(select_lag(LagCriterion::Aic, &candidates), Some(1));
+    assert_eq!(select_lag(LagCriterion::Hqic, &candidates), Some(1));
+    assert_eq!(select_lag(LagCriterion::Bic, &candidates), Some(0));
+
+    // General-to-specific: the longest lag with |t| >= 1.645, else none at all
+    let candidates = [
+        candidate(0, 100.0, 0.0),
+        candidate(1, 99.0, -3.0),
+        candidate(2, 98.0, -1.7),
+        candidate(3, 97.0, 1.6),
+    ];
+    assert_eq!(select_lag(LagCriterion::TStat, &candidates), Some(2));
+    assert_eq!(select_lag(LagCriterion::TStat, &candidates[..2]), Some(1));
+    let insignificant = [candidate(0, 100.0, 0.0), candidate(1, 99.0, 1.0)];
+    assert_eq!(select_lag(LagCriterion::TStat, &insignificant), Some(0));
+    assert_eq!(select_lag(LagCriterion::Fixed, &candidates), Some(3));
+    assert_eq!(select_lag(LagCriterion::Aic, &[]), None);
+
+    for (code, criterion) in [("AIC", LagCriterion::Aic), ("hq", LagCriterion::Hqic), ("tstat", LagCriterion::TStat), ("none", LagCriterion::Fixed)] {
+        assert_eq!(code.parse::<LagCriterion>().unwrap(), criterion);
+        assert_eq!(criterion.code().parse::<LagCriterion>().unwrap(), criterion);
+    }
+    assert!(matches!("aicc".parse::<LagCriterion>(), Err(Error::InvalidOption(_))));
+}
+
+#[test]
+fn candidates_share_the_maxlag_trimmed_sample() {
+    let data = ar2_differences(150, 9);
+    for regression in [Regression::NoConstant, Regression::Constant, Regression::ConstantTrend] {
+        let options = AdfOptions::default().with_regression(regression).with_max_lags(Some(8));
+        let result = adf_test(&data, &options).unwrap();
+        let report = result.report();
+        assert_eq!(report.ic_lags(), (0..=8).collect::<Vec<u32>>(), "{}", regression);
+        for (k, &lags) in report.ic_lags().iter().enumerate() {
+            // Every candidate drops the first 8 differences, whatever its own lag
+            let fit = adfuller_regression(&data, regression.n_terms(), lags as usize, 8);
+            assert_eq!(fit.nobs, 150 - 1 - 8);
+            let n = fit.nobs as f64;
+            let k_params = fit.params.len() as f64;
+            let llf = -n / 2.0 * ((2.0 * std::f64::consts::PI).ln() + (fit.ssr / n).ln() + 1.0);
+            let (aic, bic) = (-2.0 * llf + 2.0 * k_params, -2.0 * llf + n.ln() * k_params);
+            assert!((report.ic_aic()[k] - aic).abs() < 1e-8 * aic.abs(), "{} lag {}", regression, lags);
+            assert!((report.ic_bic()[k] - bic).abs() < 1e-8 * bic.abs(), "{} lag {}", regression, lags);
+        }
+
+        // The winner is the AIC minimum of that table, re-estimated on all the bars it can use
+        let best = (0..report.ic_aic().len())
+            .min_by(|&a, &b| report.ic_aic()[a].total_cmp(&report.ic_aic()[b]))
+            .unwrap();
+        let lags = report.ic_lags()[best];
+        assert_eq!(result.optimal_lags, lags, "{}", regression);
+        let fit = adfuller_regression(&data, regression.n_terms(), lags as usize, lags as usize);
+        assert_eq!(report.nobs as usize, 150 - 1 - lags as usize, "{}", regression);
+        assert!((report.ssr - fit.ssr).abs() < 1e-9 * fit.ssr, "{}", regression);
+    }
+}
+
+#[test]
+fn legacy_model_types_fit_the_shortest_series() {
+    let data = ar2_differences(40, 10);
+    for regression in [
+        Regression::NoConstant,
+        Regression::Constant,
+        Regression::ConstantTrend,
+        Regression::ConstantTrendSquared,
+    ] {
+        // Two observations per parameter of the lag-0 regression, and never fewer than 5
+        let shortest = (2 * (regression.n_terms() + 2)).max(5);
+        for model_type in ["ols", "kalman", "ratio"] {
+            let result = complete_adf_test(&data[..shortest], model_type, regression).unwrap();
+            assert!(result.optimal_lags <= 1, "{} {}", regression, model_type);
+        }
+        // Non-"ols" models search 0 and 1 lags
+        let report = complete_adf_test(&data, "kalman", regression).unwrap().report();
+        assert_eq!(report.ic_lags(), vec![0, 1], "{}", regression);
+    }
+}
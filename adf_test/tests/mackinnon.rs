@@ -0,0 +1,73 @@
+//! MacKinnon p-values and critical values against statsmodels' `mackinnonp` and
+//! `mackinnoncrit`.
+
+use adf_test::mackinnon::{
+    asymptotic_critical_values, cointegration_critical_values, cointegration_p_value, critical_values, mackinnonp,
+};
+use adf_test::{CriticalValues, Regression};
+
+fn assert_critical_values(actual: CriticalValues, expected: [f64; 3]) {
+    let actual = [actual.one_percent, actual.five_percent, actual.ten_percent];
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 5e-5, "{:?} vs {:?}", actual, expected);
+    }
+}
+
+#[test]
+fn p_values_match_statsmodels() {
+    // mackinnonp(-2.86, regression="c")
+    assert!((mackinnonp(-2.86, Regression::Constant) - 0.0502).abs() < 1e-4);
+    // The 5% asymptotic critical values sit at p = 0.05 for every specification
+    for regression in [
+        Regression::NoConstant,
+        Regression::Constant,
+        Regression::ConstantTrend,
+        Regression::ConstantTrendSquared,
+    ] {
+        let five_percent = asymptotic_critical_values(regression).five_percent;
+        assert!((mackinnonp(five_percent, regression) - 0.05).abs() < 2e-3, "{}", regression);
+    }
+    // Outside the fitted range the p-value is clamped
+    assert_eq!(mackinnonp(-20.0, Regression::Constant), 0.0);
+    assert_eq!(mackinnonp(3.0, Regression::Constant), 1.0);
+    assert!(mackinnonp(50.0, Regression::NoConstant) > 0.99);
+}
+
+#[test]
+fn p_values_do_not_depend_on_the_sample_size() {
+    // As in statsmodels' adfuller, only the critical values use the sample size
+    let statistic = -3.1;
+    let p = mackinnonp(statistic, Regression::Constant);
+    let adf = adf_test::get_adf_p_value_for_regression(statistic, "c").unwrap();
+    assert_eq!(adf.p_value, p);
+    assert_eq!(adf.critical_value_table(), asymptotic_critical_values(Regression::Constant));
+}
+
+#[test]
+fn critical_values_match_statsmodels() {
+    // mackinnoncrit(N=1, regression="c", nobs=100)
+    assert_critical_values(critical_values(Regression::Constant, 100), [-3.4975, -2.8909, -2.5824]);
+    // mackinnoncrit(N=1, regression="c", nobs=inf)
+    assert_critical_values(asymptotic_critical_values(Regression::Constant), [-3.43035, -2.86154, -2.56677]);
+    // mackinnoncrit(N=1, regression="ct", nobs=100), then "ctt" and "n" at nobs=250
+    assert_critical_values(critical_values(Regression::ConstantTrend, 100), [-4.0523, -3.4553, -3.1533]);
+    assert_critical_values(critical_values(Regression::ConstantTrendSquared, 250), [-4.4181, -3.8562, -3.5680]);
+    assert_critical_values(critical_values(Regression::NoConstant, 250), [-2.5747, -1.9421, -1.6158]);
+    // mackinnoncrit(N=2, regression="c", nobs=100), the Engle-Granger pairs case
+    assert_critical_values(
+        cointegration_critical_values(Regression::Constant, 2, 100).unwrap(),
+        [-4.0093, -3.3979, -3.0871],
+    );
+}
+
+#[test]
+fn cointegration_tables_cover_pairs_only() {
+    let p = cointegration_p_value(-3.34, Regression::Constant, 2).unwrap();
+    assert!((p - 0.05).abs() < 2e-3, "{}", p);
+    assert_eq!(
+        cointegration_p_value(-3.0, Regression::Constant, 1).unwrap(),
+        mackinnonp(-3.0, Regression::Constant)
+    );
+    assert!(cointegration_p_value(-3.0, Regression::NoConstant, 2).is_err());
+    assert!(cointegration_critical_values(Regression::Constant, 3, 100).is_err());
+}
@@ -0,0 +1,105 @@
+//! The QR least-squares solver on a hand-worked fit, on price-level designs whose normal
+//! equations are hopeless, and on rank-deficient designs.
+
+use adf_test::error::Error;
+use adf_test::ols::ols;
+use nalgebra::{DMatrix, DVector};
+
+mod common;
+
+use common::normals;
+
+fn design(nobs: usize, columns: &[&dyn Fn(usize) -> f64]) -> DMatrix<f64> {
+    DMatrix::from_fn(nobs, columns.len(), |i, k| columns[k](i))
+}
+
+#[test]
+fn matches_a_hand_worked_fit() {
+    // Orthogonal columns: the slope is sum(x y) / sum(x^2) = 4 / 5 and the intercept the mean
+    let x = DMatrix::from_row_slice(4, 2, &[1.0, -1.5, 1.0, -0.5, 1.0, 0.5, 1.0, 1.5]);
+    let y = DVector::from_row_slice(&[1.0, 2.0, 4.0, 3.0]);
+    let fit = ols(&x, &y).unwrap();
+    assert!((fit.params[0] - 2.5).abs() < 1e-14 && (fit.params[1] - 0.8).abs() < 1e-14);
+
+    // Residuals -0.3, -0.1, 1.1, -0.7
+    assert!((fit.ssr - 1.8).abs() < 1e-14);
+    assert_eq!((fit.nobs, fit.rank, fit.df_resid()), (4, 2, 2));
+    assert!((fit.sigma2() - 0.9).abs() < 1e-14);
+    assert!((fit.r_squared(&y, true) - 0.64).abs() < 1e-14);
+
+    // (X'X)^-1 = diag(1/4, 1/5); the condition number is the ratio of the column norms
+    let cov = fit.cov_params();
+    assert!((cov[(0, 0)] - 0.225).abs() < 1e-14 && (cov[(1, 1)] - 0.18).abs() < 1e-14);
+    assert!(cov[(0, 1)].abs() < 1e-14);
+    let std_errors = fit.std_errors();
+    for k in 0..2 {
+        assert!((std_errors[k] - cov[(k, k)].sqrt()).abs() < 1e-14);
+        assert!((fit.t_values()[k] - fit.params[k] / std_errors[k]).abs() < 1e-12);
+    }
+    assert!((fit.condition_number - 5.0_f64.sqrt() / 2.0).abs() < 1e-12);
+}
+
+#[test]
+fn recovers_coefficients_on_price_levels() {
+    // A constant, a price around 30,000 and its square: X'X has a condition number far
+    // beyond 1 / epsilon, X itself does not
+    let shocks = normals(300, 51);
+    let prices: Vec<f64> = shocks
+        .iter()
+        .scan(30_000.0, |price, e| {
+            *price += 25.0 * e;
+            Some(*price)
+        })
+        .collect();
+    let x = design(prices.len(), &[&|_| 1.0, &|i| prices[i], &|i| prices[i] * prices[i]]);
+    let beta = [4.0e3, -0.25, 4.0e-6];
+    let noise = normals(300, 52);
+    let y = DVector::from_fn(prices.len(), |i, _| {
+        beta[0] + beta[1] * prices[i] + beta[2] * prices[i] * prices[i] + 1e-3 * noise[i]
+    });
+    let fit = ols(&x, &y).unwrap();
+    assert_eq!(fit.rank, 3);
+    assert!(fit.condition_number > 1e9, "{}", fit.condition_number);
+
+    // The noise moves each coefficient by a few standard errors at most
+    let std_errors = fit.std_errors();
+    for k in 0..3 {
+        assert!((fit.params[k] - beta[k]).abs() < 5.0 * std_errors[k], "{}: {} vs {}", k, fit.params[k], beta[k]);
+    }
+    let fitted = &x * &fit.params;
+    assert!((&y - fitted).amax() < 1e-2);
+    assert!((fit.ssr - fit.residuals.norm_squared()).abs() < 1e-12);
+}
+
+#[test]
+fn rank_deficiency_is_detected() {
+    let noise = normals(60, 53);
+    let y = DVector::from_fn(60, |i, _| noise[i]);
+    let trend = |i: usize| i as f64;
+    let wiggle = |i: usize| (i as f64 * 0.7).sin();
+
+    let duplicate = design(60, &[&|_| 1.0, &trend, &trend]);
+    let combination = design(60, &[&|_| 1.0, &trend, &wiggle, &|i| 2.0 - trend(i) + 3.0 * wiggle(i)]);
+    let zero = design(60, &[&|_| 1.0, &|_| 0.0]);
+    // Collinear whatever the units: a price level next to the same level in cents
+    let rescaled = design(60, &[&|i| 3e4 + trend(i), &|i| 3e6 + 100.0 * trend(i)]);
+    for (name, x) in [("duplicate", duplicate), ("combination", combination), ("zero", zero), ("rescaled", rescaled)] {
+        assert_eq!(ols(&x, &y).err(), Some(Error::SingularDesign), "{}", name);
+    }
+
+    // Tiny units alone are not deficient
+    let tiny = design(60, &[&|_| 1.0, &|i| 1e-9 * trend(i)]);
+    let fit = ols(&tiny, &y).unwrap();
+    assert_eq!(fit.rank, 2);
+
+    let mut with_nan = y.clone();
+    with_nan[10] = f64::NAN;
+    assert_eq!(ols(&tiny, &with_nan).err(), Some(Error::SingularDesign));
+}
+
+#[test]
+fn needs_residual_degrees_of_freedom() {
+    let x = design(3, &[&|_| 1.0, &|i| i as f64, &|i| (i * i) as f64]);
+    let y = DVector::from_row_slice(&[1.0, 0.0, 2.0]);
+    assert_eq!(ols(&x, &y).err(), Some(Error::InsufficientData { required: 4, actual: 3 }));
+}
@@ -0,0 +1,125 @@
+//! Ornstein-Uhlenbeck estimates on simulated paths with known parameters.
+
+use adf_test::{ou_estimate, OuMethod, OuParameter};
+
+mod common;
+
+use common::normals;
+
+const THETA: f64 = 0.05;
+const MU: f64 = 12.0;
+const SIGMA: f64 = 0.4;
+
+/// Exact OU transitions sampled every bar, started from the stationary distribution.
+fn ou_path(n: usize, seed: u64) -> Vec<f64> {
+    let phi = (-THETA).exp();
+    let innovation_std = SIGMA * ((1.0 - phi * phi) / (2.0 * THETA)).sqrt();
+    let shocks = normals(n, seed);
+    let mut x = MU + SIGMA / (2.0 * THETA).sqrt() * shocks[0];
+    let mut path = vec![x];
+    for e in &shocks[1..] {
+        x = MU + phi * (x - MU) + innovation_std * e;
+        path.push(x);
+    }
+    path
+}
+
+#[test]
+fn both_methods_recover_the_parameters() {
+    let path = ou_path(3000, 5);
+    for method in [OuMethod::Ar1, OuMethod::Mle] {
+        let fit = ou_estimate(&path, method, 1.0).unwrap();
+        assert!(fit.is_mean_reverting);
+        for (name, parameter, truth) in [
+            ("theta", fit.theta(), THETA),
+            ("mu", fit.mu(), MU),
+            ("sigma", fit.sigma(), SIGMA),
+            ("half-life", fit.half_life(), std::f64::consts::LN_2 / THETA),
+            ("equilibrium std", fit.equilibrium_std(), SIGMA / (2.0 * THETA).sqrt()),
+        ] {
+            assert!(parameter.std_error > 0.0 && parameter.std_error.is_finite(), "{:?} {}", method, name);
+            assert!((parameter.estimate - truth).abs() < 4.0 * parameter.std_error, "{:?} {} {:?}", method, name, parameter);
+            assert!(parameter.lower < parameter.estimate && parameter.estimate < parameter.upper);
+        }
+    }
+}
+
+#[test]
+fn ar1_is_the_worker_regression() {
+    let path = ou_path(500, 11);
+    let fit = ou_estimate(&path, OuMethod::Ar1, 1.0).unwrap();
+    // The worker regresses the change on the lagged level; its slope is phi - 1
+    let (x, y): (Vec<f64>, Vec<f64>) = path.windows(2).map(|w| (w[0], w[1] - w[0])).unzip();
+    let n = x.len() as f64;
+    let (mean_x, mean_y) = (x.iter().sum::<f64>() / n, y.iter().sum::<f64>() / n);
+    let s_xy: f64 = x.iter().zip(&y).map(|(a, b)| (a - mean_x) * (b - mean_y)).sum();
+    let s_xx: f64 = x.iter().map(|a| (a - mean_x).powi(2)).sum();
+    assert!((fit.phi().estimate - (1.0 + s_xy / s_xx)).abs() < 1e-10);
+
+    // With bars five time units apart the rate per unit falls and the half-life grows
+    let spaced = ou_estimate(&path, OuMethod::Ar1, 5.0).unwrap();
+    assert!((spaced.theta().estimate * 5.0 - fit.theta().estimate).abs() < 1e-12);
+    assert!((spaced.half_life().estimate - 5.0 * fit.half_life().estimate).abs() < 1e-9);
+}
+
+#[test]
+fn a_random_walk_has_an_unbounded_half_life() {
+    let walk: Vec<f64> = normals(400, 3).iter().scan(50.0, |level, e| {
+        *level += e;
+        Some(*level)
+    }).collect();
+    let fit = ou_estimate(&walk, OuMethod::Mle, 1.0).unwrap();
+    assert!(!fit.is_mean_reverting);
+    assert_eq!(fit.half_life().upper, f64::INFINITY);
+    assert!(ou_estimate(&walk[..10], OuMethod::Ar1, 1.0).is_err());
+    assert!("euler".parse::<OuMethod>().is_err());
+}
+
+/// AR(1) `x_t = mu + phi * (x_{t-1} - mu) + e_t` with unit shocks, started at `x_0`.
+fn ar1_path(phi: f64, mu: f64, x_0: f64, n: usize, seed: u64) -> Vec<f64> {
+    normals(n, seed)
+        .iter()
+        .scan(x_0, |x, e| {
+            *x = mu + phi * (*x - mu) + e;
+            Some(*x)
+        })
+        .collect()
+}
+
+fn is_undefined(parameter: OuParameter) -> bool {
+    [parameter.estimate, parameter.std_error, parameter.lower, parameter.upper]
+        .iter()
+        .all(|v| v.is_nan())
+}
+
+#[test]
+fn a_negatively_autocorrelated_spread_has_no_ou_rate() {
+    let path = ar1_path(-0.5, 3.0, 3.0, 1000, 8);
+    for method in [OuMethod::Ar1, OuMethod::Mle] {
+        let fit = ou_estimate(&path, method, 1.0).unwrap();
+        assert!(!fit.is_mean_reverting, "{:?}", method);
+        assert!((fit.phi().estimate + 0.5).abs() < 0.1, "{:?}", method);
+        assert!(is_undefined(fit.theta()) && is_undefined(fit.sigma()) && is_undefined(fit.half_life()));
+        // Still a stationary AR(1): mean 3 and standard deviation 1 / sqrt(1 - 0.25)
+        assert!((fit.mu().estimate - 3.0).abs() < 4.0 * fit.mu().std_error, "{:?}", method);
+        let equilibrium_std = fit.equilibrium_std();
+        assert!(equilibrium_std.std_error > 0.0 && equilibrium_std.std_error.is_finite());
+        assert!((equilibrium_std.estimate - 0.75_f64.sqrt().recip()).abs() < 4.0 * equilibrium_std.std_error);
+    }
+}
+
+#[test]
+fn an_explosive_spread_only_reports_phi() {
+    let path = ar1_path(1.02, 0.0, 1.0, 300, 9);
+    let fit = ou_estimate(&path, OuMethod::Ar1, 1.0).unwrap();
+    assert!(!fit.is_mean_reverting);
+    assert!(fit.phi().estimate > 1.0 && fit.phi().std_error > 0.0);
+    for parameter in [fit.theta(), fit.mu(), fit.sigma(), fit.half_life(), fit.equilibrium_std()] {
+        assert!(is_undefined(parameter), "{:?}", parameter);
+    }
+    // The exact likelihood keeps phi below 1, so it reports a rate the data cannot support
+    let fit = ou_estimate(&path, OuMethod::Mle, 1.0).unwrap();
+    assert!(!fit.is_mean_reverting);
+    assert!(fit.phi().estimate < 1.0 && fit.phi().upper > 1.0);
+    assert_eq!(fit.half_life().upper, f64::INFINITY);
+}
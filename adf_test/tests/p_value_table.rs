@@ -0,0 +1,60 @@
+//! Parity between the compact p-value table and the original 50,000-row lookup table.
+
+use adf_test::p_value_table::{knot_count, P_VALUE_TOLERANCE};
+use adf_test::interpolate_p_value;
+
+fn original_table() -> Vec<[f64; 2]> {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/data/adf_p_value_lookup.csv");
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .skip(1)
+        .map(|line| {
+            let (x, p) = line.split_once(',').unwrap();
+            [x.parse().unwrap(), p.parse().unwrap()]
+        })
+        .collect()
+}
+
+/// The interpolation `lib.rs` used to run against the full table.
+fn interpolate_original(table: &[[f64; 2]], test_statistic: f64) -> f64 {
+    if test_statistic <= table[0][0] {
+        return table[0][1];
+    }
+    if test_statistic >= table[table.len() - 1][0] {
+        return table[table.len() - 1][1];
+    }
+    let idx = table.partition_point(|row| row[0] <= test_statistic) - 1;
+    let [x1, y1] = table[idx];
+    let [x2, y2] = table[idx + 1];
+    y1 + (test_statistic - x1) * (y2 - y1) / (x2 - x1)
+}
+
+#[test]
+fn compact_table_matches_original_within_tolerance() {
+    let table = original_table();
+    assert_eq!(table.len(), 50_000);
+    assert!(knot_count() < table.len() / 20);
+
+    let mut probes: Vec<f64> = table.iter().map(|row| row[0]).collect();
+    probes.extend(table.windows(2).map(|w| 0.5 * (w[0][0] + w[1][0])));
+    probes.extend((0..=1000).map(|i| -6.0 + 9.0 * i as f64 / 1000.0));
+
+    let worst = probes
+        .iter()
+        .map(|&x| (interpolate_p_value(x) - interpolate_original(&table, x)).abs())
+        .fold(0.0, f64::max);
+    assert!(
+        worst <= P_VALUE_TOLERANCE + 1e-12,
+        "max deviation {} exceeds {}",
+        worst,
+        P_VALUE_TOLERANCE
+    );
+}
+
+#[test]
+fn compact_table_keeps_the_original_bounds() {
+    let table = original_table();
+    assert_eq!(interpolate_p_value(-10.0), table[0][1]);
+    assert_eq!(interpolate_p_value(10.0), table[table.len() - 1][1]);
+}
@@ -0,0 +1,141 @@
+//! Phillips-Perron against a case worked by hand and arch's `PhillipsPerron`, transcribed
+//! below: levels regressed on lagged levels, with the statistics corrected by
+//! `cov_nw(u, lags, demean=False)`.
+
+use adf_test::long_run_variance::Bandwidth;
+use adf_test::mackinnon::{critical_values, mackinnonp};
+use adf_test::{pp_test, Regression};
+use nalgebra::{DMatrix, DVector};
+
+mod common;
+
+use common::normals;
+
+/// `(Z(t), Z(alpha), lags)` of `PhillipsPerron(y, lags, trend)`; `lags` of `None` is arch's
+/// default `int(ceil(12 * (nobs / 100)^(1/4)))`.
+fn arch_phillips_perron(y: &[f64], n_trend: usize, lags: Option<usize>) -> (f64, f64, usize) {
+    let nobs = y.len();
+    let lags = lags.unwrap_or_else(|| (12.0 * (nobs as f64 / 100.0).powf(0.25)).ceil() as usize);
+    // rhs = [y_{t-1}, const, trend]
+    let n = nobs - 1;
+    let rhs = DMatrix::from_fn(n, 1 + n_trend, |i, c| match c {
+        0 => y[i],
+        1 => 1.0,
+        _ => (i + 1) as f64,
+    });
+    let lhs = DVector::from_fn(n, |i, _| y[i + 1]);
+    let xtx_inv = (rhs.transpose() * &rhs).try_inverse().unwrap();
+    let params = &xtx_inv * rhs.transpose() * &lhs;
+    let u = &lhs - &rhs * &params;
+
+    let k = rhs.ncols() as f64;
+    let n = n as f64;
+    let mut lam2 = u.dot(&u);
+    for j in 1..=lags {
+        let w = 1.0 - j as f64 / (lags as f64 + 1.0);
+        lam2 += 2.0 * w * u.rows(j, u.len() - j).dot(&u.rows(0, u.len() - j));
+    }
+    lam2 /= n;
+    let lam = lam2.sqrt();
+    let s2 = u.dot(&u) / (n - k);
+    let s = s2.sqrt();
+    let gamma0 = s2 * (n - k) / n;
+    let sigma = (s2 * xtx_inv[(0, 0)]).sqrt();
+    let rho = params[0];
+
+    let t = (rho - 1.0) / sigma;
+    let tau = (gamma0 / lam2).sqrt() * t - 0.5 * ((lam2 - gamma0) / lam) * (n * sigma / s);
+    let z = n * (rho - 1.0) - 0.5 * (n * n * sigma * sigma / s2) * (lam2 - gamma0);
+    (tau, z, lags)
+}
+
+fn close(actual: f64, expected: f64) -> bool {
+    (actual - expected).abs() < 1e-8 * expected.abs().max(1.0)
+}
+
+#[test]
+fn matches_a_hand_worked_case() {
+    // Without deterministic terms: differences (1, -1, 2, -1) on levels (1, 2, 1, 3) give
+    // gamma = -2 / 15, residuals (17, -11, 32, -9) / 15 and ssr = 7 - 4 / 15 = 101 / 15
+    let y = [1.0, 2.0, 1.0, 3.0, 2.0];
+
+    // With no lags the long-run variance is gamma0 and there is nothing to correct:
+    // Z(t) is gamma / sqrt(s2 / 15) = -2 sqrt(3 / 101) and Z(alpha) is n gamma
+    let result = pp_test(&y, Regression::NoConstant, Bandwidth::Fixed(0)).unwrap();
+    assert!((result.test_statistic + 2.0 * (3.0_f64 / 101.0).sqrt()).abs() < 1e-12, "{}", result.test_statistic);
+    assert!((result.z_alpha + 8.0 / 15.0).abs() < 1e-12, "{}", result.z_alpha);
+
+    // One lag: the first autocovariance -827 / 225 at weight 1/2 leaves lambda2 = 688 / 900
+    // against gamma0 = 1515 / 900, so
+    //   Z(alpha) = -8 / 15 + (16 / 30) * 827 / 900 = -146 / 3375
+    //   Z(t) = sqrt(1515 / 688) * -2 sqrt(3 / 101) + 827 / (15 sqrt(10320))
+    let result = pp_test(&y, Regression::NoConstant, Bandwidth::Fixed(1)).unwrap();
+    let z_t = -2.0 * (45.0_f64 / 688.0).sqrt() + 827.0 / (15.0 * 10320.0_f64.sqrt());
+    assert!((result.test_statistic - z_t).abs() < 1e-12, "{}", result.test_statistic);
+    assert!((result.test_statistic - 0.0312224654).abs() < 1e-10);
+    assert!((result.z_alpha + 146.0 / 3375.0).abs() < 1e-12, "{}", result.z_alpha);
+    assert_eq!((result.lags, result.nobs), (1, 4));
+}
+
+#[test]
+fn matches_arch() {
+    let shocks = normals(300, 21);
+    let mut ma = 0.0;
+    // A random walk with MA(1) errors, where the correction matters
+    let walk: Vec<f64> = shocks
+        .iter()
+        .scan(50.0, |level, e| {
+            *level += e + 0.5 * ma;
+            ma = *e;
+            Some(*level)
+        })
+        .collect();
+    let mut ar = 0.0;
+    let stationary: Vec<f64> = shocks
+        .iter()
+        .enumerate()
+        .map(|(i, e)| {
+            ar = 0.7 * ar + e;
+            20.0 + 0.01 * i as f64 + ar
+        })
+        .collect();
+
+    for (name, series) in [("walk", &walk), ("stationary", &stationary)] {
+        for regression in [Regression::NoConstant, Regression::Constant, Regression::ConstantTrend] {
+            for (bandwidth, lags) in [(Bandwidth::Schwert, None), (Bandwidth::Fixed(3), Some(3))] {
+                let (tau, z, lags) = arch_phillips_perron(series, regression.n_terms(), lags);
+                let result = pp_test(series, regression, bandwidth).unwrap();
+                let case = format!("{} {} {:?}", name, regression, bandwidth);
+                assert_eq!(result.lags as usize, lags, "{}", case);
+                assert_eq!(result.nobs as usize, series.len() - 1, "{}", case);
+                assert!(close(result.test_statistic, tau), "{}: {} vs {}", case, result.test_statistic, tau);
+                assert!(close(result.z_alpha, z), "{}: {} vs {}", case, result.z_alpha, z);
+                // arch's p-value and critical values are MacKinnon's for Z(t)
+                assert_eq!(result.p_value, mackinnonp(result.test_statistic, regression), "{}", case);
+                assert_eq!(result.critical_value_table(), critical_values(regression, series.len() - 1));
+            }
+        }
+    }
+
+    // 300 bars: ceil(12 * 3^(1/4)) = 16 lags by default
+    let result = pp_test(&walk, Regression::Constant, Bandwidth::Schwert).unwrap();
+    assert_eq!(result.lags, 16);
+    assert!(!result.is_stationary);
+    assert!(pp_test(&stationary, Regression::ConstantTrend, Bandwidth::Schwert).unwrap().is_stationary);
+}
+
+#[test]
+fn z_alpha_critical_values_come_from_fullers_table() {
+    let series: Vec<f64> = normals(101, 4)
+        .iter()
+        .scan(0.0, |level, e| {
+            *level += e;
+            Some(*level)
+        })
+        .collect();
+    // 100 observations in the regression: exactly Fuller's T = 100 row
+    let cv = pp_test(&series, Regression::Constant, Bandwidth::Schwert).unwrap().z_alpha_critical_value_table();
+    assert!(close(cv.one_percent, -19.8) && close(cv.five_percent, -13.7) && close(cv.ten_percent, -11.0));
+    let cv = pp_test(&series, Regression::ConstantTrend, Bandwidth::Schwert).unwrap().z_alpha_critical_value_table();
+    assert!(close(cv.one_percent, -27.4) && close(cv.five_percent, -20.7) && close(cv.ten_percent, -17.5));
+}
@@ -0,0 +1,77 @@
+//! Recursive least squares against direct weighted regressions.
+
+use adf_test::{rls_hedge, rolling_ols, RecursiveLeastSquares};
+
+mod common;
+
+use common::Lcg;
+
+fn pair(n: usize) -> (Vec<f64>, Vec<f64>) {
+    let mut rng = Lcg::new(31337);
+    let mut b = 60.0;
+    let mut prices_a = Vec::with_capacity(n);
+    let mut prices_b = Vec::with_capacity(n);
+    for i in 0..n {
+        b += rng.centred();
+        prices_b.push(b);
+        prices_a.push(3.0 + (0.8 + 0.4 * i as f64 / n as f64) * b + 0.2 * rng.centred());
+    }
+    (prices_a, prices_b)
+}
+
+/// Exponentially weighted least squares of the first `end` bars.
+fn weighted_fit(prices_a: &[f64], prices_b: &[f64], end: usize, lambda: f64) -> (f64, f64) {
+    let weights: Vec<f64> = (0..end).map(|j| lambda.powi((end - 1 - j) as i32)).collect();
+    let total: f64 = weights.iter().sum();
+    let mean_a = (0..end).map(|j| weights[j] * prices_a[j]).sum::<f64>() / total;
+    let mean_b = (0..end).map(|j| weights[j] * prices_b[j]).sum::<f64>() / total;
+    let s_ab: f64 = (0..end).map(|j| weights[j] * (prices_a[j] - mean_a) * (prices_b[j] - mean_b)).sum();
+    let s_bb: f64 = (0..end).map(|j| weights[j] * (prices_b[j] - mean_b).powi(2)).sum();
+    let beta = s_ab / s_bb;
+    (mean_a - beta * mean_b, beta)
+}
+
+#[test]
+fn without_forgetting_it_is_expanding_ols() {
+    let (prices_a, prices_b) = pair(300);
+    let rls = rls_hedge(&prices_a, &prices_b, 1.0, Some(1e8)).unwrap().series();
+    let expanding = rolling_ols(&prices_a, &prices_b, prices_a.len()).unwrap().series();
+    for i in 30..prices_a.len() {
+        assert!((rls.betas()[i] - expanding.betas()[i]).abs() < 1e-5, "bar {}", i);
+        assert!((rls.alphas()[i] - expanding.alphas()[i]).abs() < 1e-3, "bar {}", i);
+    }
+}
+
+#[test]
+fn forgetting_matches_weighted_least_squares() {
+    let (prices_a, prices_b) = pair(400);
+    let lambda = 0.97;
+    let rls = rls_hedge(&prices_a, &prices_b, lambda, Some(1e8)).unwrap();
+    let series = rls.series();
+    for end in [100, 250, 400] {
+        let (alpha, beta) = weighted_fit(&prices_a, &prices_b, end, lambda);
+        assert!((series.betas()[end - 1] - beta).abs() < 1e-6, "bar {}", end - 1);
+        assert!((series.alphas()[end - 1] - alpha).abs() < 1e-4, "bar {}", end - 1);
+    }
+    assert_eq!(rls.series().z_scores(30).len(), 400);
+}
+
+#[test]
+fn bar_by_bar_updates_match_the_batch() {
+    let (prices_a, prices_b) = pair(200);
+    let batch = rls_hedge(&prices_a, &prices_b, 0.99, None).unwrap();
+    let mut live = rls_hedge(&prices_a[..120], &prices_b[..120], 0.99, None).unwrap();
+    for i in 120..200 {
+        let step = live.step(prices_a[i], prices_b[i]).unwrap();
+        assert_eq!(step.beta, batch.series().betas()[i]);
+        assert_eq!(step.prediction_error, batch.prediction_errors()[i]);
+        assert_eq!(step.spread, batch.series().spreads()[i]);
+    }
+    assert_eq!(live.series().spreads(), batch.series().spreads());
+    assert_eq!(live.gains_beta(), batch.gains_beta());
+
+    assert!(live.step(f64::NAN, 1.0).is_err());
+    assert_eq!(live.bars(), 200);
+    assert!(RecursiveLeastSquares::with_forgetting_factor(1.5, None).is_err());
+    assert!(RecursiveLeastSquares::with_forgetting_factor(0.0, None).is_err());
+}
@@ -0,0 +1,103 @@
+//! Robust hedge ratios on a pair with a handful of gross price glitches.
+
+use adf_test::error::Error;
+use adf_test::robust::MAX_THEIL_SEN_BARS;
+use adf_test::{robust_hedge, rolling_ols, rolling_robust_hedge, RobustMethod};
+
+mod common;
+
+use common::Lcg;
+
+const GLITCHES: [usize; 6] = [40, 41, 97, 150, 151, 220];
+
+fn glitched_pair(n: usize) -> (Vec<f64>, Vec<f64>) {
+    let mut rng = Lcg::new(99);
+    let mut b = 30.0;
+    let mut prices_a = Vec::with_capacity(n);
+    let mut prices_b = Vec::with_capacity(n);
+    for _ in 0..n {
+        b += rng.centred();
+        prices_b.push(b);
+        prices_a.push(4.0 + 2.0 * b + 0.5 * rng.centred());
+    }
+    for &i in &GLITCHES {
+        prices_a[i] += 25.0;
+    }
+    (prices_a, prices_b)
+}
+
+fn absolute_loss(prices_a: &[f64], prices_b: &[f64], alpha: f64, beta: f64) -> f64 {
+    prices_a.iter().zip(prices_b).map(|(a, b)| (a - alpha - beta * b).abs()).sum()
+}
+
+#[test]
+fn robust_fits_ignore_the_glitches() {
+    let (prices_a, prices_b) = glitched_pair(250);
+    let ols = rolling_ols(&prices_a, &prices_b, prices_a.len()).unwrap().series();
+    let ols_beta = *ols.betas().last().unwrap();
+    for method in [RobustMethod::Huber, RobustMethod::TheilSen, RobustMethod::Lad] {
+        let fit = robust_hedge(&prices_a, &prices_b, method).unwrap();
+        assert!((fit.beta - 2.0).abs() < (ols_beta - 2.0).abs(), "{:?}", method);
+        assert!((fit.beta - 2.0).abs() < 0.03, "{:?} beta {}", method, fit.beta);
+        for (i, &flag) in fit.outlier_flags().iter().enumerate() {
+            assert_eq!(flag, GLITCHES.contains(&i), "{:?} bar {}", method, i);
+        }
+        for &i in &GLITCHES {
+            assert!(fit.weight_values()[i] < 0.1, "{:?} bar {}", method, i);
+        }
+    }
+}
+
+#[test]
+fn theil_sen_and_lad_solve_their_problems() {
+    let prices_b = [1.0, 2.0, 3.0, 4.0, 5.0, 5.0];
+    let prices_a = [2.0, 4.5, 5.5, 9.0, 10.0, 30.0];
+    // The 14 slopes between distinct B prices have 2.25 and 7/3 in the middle, and the
+    // intercepts a - beta * b then have -7/24 and -4/24 in the middle
+    let theil_sen = robust_hedge(&prices_a, &prices_b, RobustMethod::TheilSen).unwrap();
+    assert!((theil_sen.beta - 55.0 / 24.0).abs() < 1e-12, "{}", theil_sen.beta);
+    assert!((theil_sen.alpha + 11.0 / 48.0).abs() < 1e-12, "{}", theil_sen.alpha);
+
+    let (prices_a, prices_b) = glitched_pair(240);
+    let lad = robust_hedge(&prices_a, &prices_b, RobustMethod::Lad).unwrap();
+    let best = absolute_loss(&prices_a, &prices_b, lad.alpha, lad.beta);
+    for (da, db) in [(1e-3, 0.0), (-1e-3, 0.0), (0.0, 1e-4), (0.0, -1e-4), (1e-3, -1e-4)] {
+        assert!(best <= absolute_loss(&prices_a, &prices_b, lad.alpha + da, lad.beta + db) + 1e-6);
+    }
+    assert!("median".parse::<RobustMethod>().is_err());
+}
+
+#[test]
+fn rolling_matches_a_fit_of_the_last_window() {
+    let (prices_a, prices_b) = glitched_pair(260);
+    for method in [RobustMethod::Huber, RobustMethod::TheilSen, RobustMethod::Lad] {
+        let rolling = rolling_robust_hedge(&prices_a, &prices_b, 50, method).unwrap();
+        let series = rolling.series();
+        assert_eq!((series.alphas()[0], series.betas()[0]), (0.0, 1.0));
+        let last = robust_hedge(&prices_a[210..], &prices_b[210..], method).unwrap();
+        assert_eq!(series.betas()[259], last.beta);
+        assert!(rolling.outlier_flags()[220], "{:?}", method);
+        assert!(!rolling.outlier_flags()[221], "{:?}", method);
+    }
+}
+
+#[test]
+fn theil_sen_refuses_samples_with_too_many_pairs() {
+    let (prices_a, prices_b) = glitched_pair(MAX_THEIL_SEN_BARS + 1);
+    let error = robust_hedge(&prices_a, &prices_b, RobustMethod::TheilSen).err().unwrap();
+    assert!(matches!(error, Error::InvalidOption(_)));
+    assert_eq!(
+        error.to_string(),
+        "invalid option: Theil-Sen is limited to 2000 bars, got 2001; use \"huber\" or \"lad\" for longer samples"
+    );
+    assert!(robust_hedge(&prices_a[1..], &prices_b[1..], RobustMethod::TheilSen).is_ok());
+    assert!(robust_hedge(&prices_a, &prices_b, RobustMethod::Huber).is_ok());
+
+    // The window is checked before any fit, even when the series is shorter than it
+    let window = MAX_THEIL_SEN_BARS + 1;
+    assert!(matches!(
+        rolling_robust_hedge(&prices_a[..300], &prices_b[..300], window, RobustMethod::TheilSen),
+        Err(Error::InvalidOption(_))
+    ));
+    assert!(rolling_robust_hedge(&prices_a[..300], &prices_b[..300], window, RobustMethod::Lad).is_ok());
+}
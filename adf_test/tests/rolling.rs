@@ -0,0 +1,86 @@
+//! The incremental rolling and expanding ADF must agree with testing each window on its own.
+
+use adf_test::{adf_test, expanding_adf, rolling_adf, AdfOptions, LagCriterion, Regression};
+
+mod common;
+
+use common::{normals, Lcg};
+
+/// An AR(1) with coefficient 0.95 around a level of 50, driven by a fixed LCG.
+fn persistent_series(n: usize) -> Vec<f64> {
+    let mut rng = Lcg::new(987654321);
+    let mut value = 0.0;
+    (0..n)
+        .map(|_| {
+            value = 0.95 * value + rng.centred();
+            50.0 + value
+        })
+        .collect()
+}
+
+/// A price random walk around 50,000 moving by about 25 per bar.
+fn price_levels(n: usize) -> Vec<f64> {
+    normals(n, 71)
+        .iter()
+        .scan(50_000.0, |price, e| {
+            *price += 25.0 * e;
+            Some(*price)
+        })
+        .collect()
+}
+
+fn assert_matches_windows(data: &[f64], windows: &[(usize, usize)], options: &AdfOptions, result: &adf_test::RollingAdfResult) {
+    assert_eq!(result.len(), windows.len());
+    let statistics = result.test_statistics();
+    let p_values = result.p_values();
+    let lags = result.lags();
+    for (i, &(start, end)) in windows.iter().enumerate() {
+        let expected = adf_test(&data[start..end], options).unwrap();
+        assert_eq!(lags[i], expected.optimal_lags, "window {}..{}", start, end);
+        assert!((statistics[i] - expected.test_statistic).abs() < 1e-6, "window {}..{}", start, end);
+        assert!((p_values[i] - expected.p_value).abs() < 1e-6);
+        assert_eq!(result.stationary()[i], expected.is_stationary);
+    }
+}
+
+#[test]
+fn rolling_matches_individual_windows() {
+    let data = persistent_series(400);
+    for regression in [Regression::NoConstant, Regression::Constant, Regression::ConstantTrend] {
+        for autolag in [LagCriterion::Aic, LagCriterion::TStat, LagCriterion::Fixed] {
+            let options = AdfOptions::new().with_regression(regression).with_autolag(autolag);
+            let result = rolling_adf(&data, 120, 7, &options).unwrap();
+            let windows: Vec<(usize, usize)> = (0..=data.len() - 120).step_by(7).map(|s| (s, s + 120)).collect();
+            assert_matches_windows(&data, &windows, &options, &result);
+        }
+    }
+}
+
+#[test]
+fn expanding_matches_individual_windows() {
+    let data = persistent_series(300);
+    let options = AdfOptions::new().with_regression(Regression::ConstantTrend);
+    let result = expanding_adf(&data, 60, 11, &options).unwrap();
+    let windows: Vec<(usize, usize)> = (60..=data.len()).step_by(11).map(|end| (0, end)).collect();
+    assert_matches_windows(&data, &windows, &options, &result);
+}
+
+#[test]
+fn cross_products_match_qr_at_price_levels() {
+    // Within a window the level moves by a fraction of a percent, so X'X of the raw prices
+    // would be singular to working precision; the standardised, rescaled cross-products
+    // must still agree with the QR fit of each window
+    let data = price_levels(300);
+    for regression in [Regression::NoConstant, Regression::Constant, Regression::ConstantTrend] {
+        for autolag in [LagCriterion::Aic, LagCriterion::Fixed] {
+            let options = AdfOptions::new().with_regression(regression).with_autolag(autolag);
+            let result = rolling_adf(&data, 80, 7, &options).unwrap();
+            let windows: Vec<(usize, usize)> = (0..=data.len() - 80).step_by(7).map(|s| (s, s + 80)).collect();
+            assert_matches_windows(&data, &windows, &options, &result);
+
+            let result = expanding_adf(&data, 60, 17, &options).unwrap();
+            let windows: Vec<(usize, usize)> = (60..=data.len()).step_by(17).map(|end| (0, end)).collect();
+            assert_matches_windows(&data, &windows, &options, &result);
+        }
+    }
+}
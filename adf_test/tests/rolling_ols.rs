@@ -0,0 +1,78 @@
+//! The incremental rolling OLS must reproduce the worker's per-bar regression.
+
+use adf_test::rolling_ols;
+
+mod common;
+
+use common::Lcg;
+
+/// The worker's `calculateHedgeRatio` for one bar, refitting the window from scratch with
+/// two-pass centred sums so that the reference itself is accurate.
+fn worker_hedge_ratio(prices_a: &[f64], prices_b: &[f64], index: usize, window: usize) -> (f64, f64) {
+    let start = (index + 1).saturating_sub(window);
+    let (a, b) = (&prices_a[start..=index], &prices_b[start..=index]);
+    let count = a.len() as f64;
+    let mean_a = a.iter().sum::<f64>() / count;
+    let mean_b = b.iter().sum::<f64>() / count;
+    let s_bb: f64 = b.iter().map(|v| (v - mean_b).powi(2)).sum();
+    if s_bb == 0.0 {
+        return (0.0, 1.0);
+    }
+    let s_ab: f64 = a.iter().zip(b).map(|(x, y)| (x - mean_a) * (y - mean_b)).sum();
+    let beta = s_ab / s_bb;
+    (mean_a - beta * mean_b, beta)
+}
+
+fn pair(n: usize, level: f64) -> (Vec<f64>, Vec<f64>) {
+    let mut rng = Lcg::new(42);
+    let mut b = level;
+    let mut prices_a = Vec::with_capacity(n);
+    let mut prices_b = Vec::with_capacity(n);
+    for i in 0..n {
+        b += rng.centred() * level / 50.0;
+        prices_b.push(b);
+        prices_a.push(0.1 * level + (1.0 + i as f64 / n as f64) * b + rng.centred() * level / 100.0);
+    }
+    (prices_a, prices_b)
+}
+
+#[test]
+fn matches_the_worker_including_the_warm_up() {
+    for level in [20.0, 4000.0] {
+        let (prices_a, prices_b) = pair(700, level);
+        for window in [2, 30, 250] {
+            let result = rolling_ols(&prices_a, &prices_b, window).unwrap();
+            let series = result.series();
+            assert_eq!(series.len(), prices_a.len());
+            // The first bar is a single point, where the worker falls back to beta = 1
+            assert_eq!((series.alphas()[0], series.betas()[0]), (0.0, 1.0));
+            assert!(result.r_squared()[0].is_nan());
+            for i in 1..prices_a.len() {
+                let (alpha, beta) = worker_hedge_ratio(&prices_a, &prices_b, i, window);
+                // Two-bar windows can be badly conditioned, so errors scale with |beta|
+                let tolerance = 1e-7 * beta.abs().max(1.0);
+                assert!((series.betas()[i] - beta).abs() < tolerance, "bar {} window {}", i, window);
+                assert!((series.alphas()[i] - alpha).abs() < tolerance * level, "bar {} window {}", i, window);
+                let spread = prices_a[i] - (alpha + beta * prices_b[i]);
+                assert!((series.spreads()[i] - spread).abs() < tolerance * level);
+                let r2 = result.r_squared()[i];
+                assert!((0.0..=1.0).contains(&r2), "bar {} window {} r2 {}", i, window, r2);
+            }
+        }
+    }
+}
+
+#[test]
+fn constant_leg_falls_back_to_unit_hedge() {
+    let prices_b = [10.0, 10.0, 10.0, 10.0, 11.0, 12.0, 12.0, 12.0, 12.0];
+    let prices_a = [5.0, 6.0, 7.0, 6.0, 8.0, 9.0, 9.5, 9.0, 9.2];
+    let result = rolling_ols(&prices_a, &prices_b, 3).unwrap();
+    let series = result.series();
+    for i in [0, 1, 2, 3, 8] {
+        assert_eq!((series.alphas()[i], series.betas()[i]), (0.0, 1.0), "bar {}", i);
+        assert_eq!(series.spreads()[i], prices_a[i] - prices_b[i]);
+    }
+    assert!((series.betas()[4] - 1.5).abs() < 1e-12);
+    assert!(rolling_ols(&prices_a, &prices_b, 1).is_err());
+    assert!(rolling_ols(&prices_a, &prices_b[1..], 3).is_err());
+}
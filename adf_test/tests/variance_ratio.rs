@@ -0,0 +1,123 @@
+//! Variance ratios against arch's `VarianceRatio(y, lags=q, trend="c", debiased=True,
+//! overlap=True)`, with and without `robust`, plus cases worked by hand.
+
+use adf_test::variance_ratio_test;
+
+mod common;
+
+use common::normals;
+
+/// `(vr, stat)` of arch's `VarianceRatio`.
+fn arch_variance_ratio(y: &[f64], q: usize, robust: bool) -> (f64, f64) {
+    let nobs = y.len();
+    let mu = (y[nobs - 1] - y[0]) / (nobs - 1) as f64;
+    let delta_y: Vec<f64> = y.windows(2).map(|w| w[1] - w[0]).collect();
+    let nq = delta_y.len() as f64;
+    let sigma2_1 = delta_y.iter().map(|d| (d - mu).powi(2)).sum::<f64>() / (nq - 1.0);
+    let qf = q as f64;
+    let m = qf * (nq - qf + 1.0) * (1.0 - qf / nq);
+    let sigma2_q = (q..nobs).map(|t| (y[t] - y[t - q] - qf * mu).powi(2)).sum::<f64>() / m;
+    let vr = sigma2_q / sigma2_1;
+    let theta = if robust {
+        let z2: Vec<f64> = delta_y.iter().map(|d| (d - mu).powi(2)).collect();
+        let scale = z2.iter().sum::<f64>().powi(2);
+        (1..q)
+            .map(|k| {
+                let delta = nq * (k..z2.len()).map(|t| z2[t] * z2[t - k]).sum::<f64>() / scale;
+                4.0 * (1.0 - k as f64 / qf).powi(2) * delta
+            })
+            .sum()
+    } else {
+        2.0 * (2.0 * qf - 1.0) * (qf - 1.0) / (3.0 * qf)
+    };
+    (vr, nq.sqrt() * (vr - 1.0) / theta.sqrt())
+}
+
+#[test]
+fn matches_a_hand_worked_case() {
+    // Increments 1, 2, -1, 2 with mean 1: sigma_a = 6 / 3, two-period sum of squares 3 / 3
+    let result = variance_ratio_test(&[0.0, 1.0, 3.0, 2.0, 4.0], &[2]).unwrap();
+    assert!((result.variance_ratios()[0] - 0.5).abs() < 1e-12);
+    assert!((result.z_statistics()[0] + 1.0).abs() < 1e-12);
+    // theta = 4 * (0 + 4 + 4) / 36
+    assert!((result.robust_z_statistics()[0] + 1.5 / 2.0_f64.sqrt()).abs() < 1e-12);
+    // 2 * (1 - Phi(1)), to the accuracy of the erfc approximation
+    assert!((result.p_values()[0] - 0.317_310_507_862_914_1).abs() < 1e-7);
+    assert!((result.chow_denning - 1.0).abs() < 1e-12);
+    assert!((result.chow_denning_p_value - result.p_values()[0]).abs() < 1e-15);
+}
+
+#[test]
+fn matches_a_hand_worked_pair_of_horizons() {
+    // Increments (2, -1, 3, -1, 2, -1, 3) with mean 1: sigma_a = 22 / 6. Both horizons have
+    // m = 60 / 7; the centred two- and three-period sums of squares are 3 and 16
+    let result = variance_ratio_test(&[0.0, 2.0, 1.0, 4.0, 3.0, 5.0, 4.0, 7.0], &[2, 3]).unwrap();
+    let ratios = result.variance_ratios();
+    assert!((ratios[0] - 21.0 / 220.0).abs() < 1e-12 && (ratios[1] - 28.0 / 55.0).abs() < 1e-12, "{:?}", ratios);
+
+    // theta is 1 at q = 2 and 20 / 9 at q = 3
+    let z = result.z_statistics();
+    let sqrt7 = 7.0_f64.sqrt();
+    assert!((z[0] + sqrt7 * 199.0 / 220.0).abs() < 1e-12);
+    assert!((z[1] + sqrt7 * 27.0 / 55.0 / (20.0_f64 / 9.0).sqrt()).abs() < 1e-12);
+
+    // Squared deviations (1, 4, 4, 4, 1, 4, 4) give delta_1 = 105 / 121 and
+    // delta_2 = 7 / 11, so theta* is 105 / 121 at q = 2 and 1988 / 1089 at q = 3
+    let robust = result.robust_z_statistics();
+    assert!((robust[0] + 199.0 / (20.0 * 15.0_f64.sqrt())).abs() < 1e-12, "{:?}", robust);
+    assert!((robust[1] + 81.0 / (10.0 * 71.0_f64.sqrt())).abs() < 1e-12, "{:?}", robust);
+
+    // The two-period ratio decides both joint tests, with the Sidak bound over two horizons
+    assert_eq!(result.chow_denning, z[0].abs());
+    assert!((result.chow_denning_p_value - (1.0 - (1.0 - result.p_values()[0]).powi(2))).abs() < 1e-12);
+    assert_eq!(result.chow_denning_robust, robust[0].abs());
+    let single = result.robust_p_values()[0];
+    assert!((result.chow_denning_robust_p_value - (1.0 - (1.0 - single).powi(2))).abs() < 1e-12);
+    // 2 * (1 - Phi(2.5692)) = 0.0102, and 1 - (1 - 0.0102)^2 = 0.0203
+    assert!((single - 0.010197).abs() < 1e-6, "{}", single);
+    assert!(result.is_mean_reverting);
+}
+
+#[test]
+fn matches_arch() {
+    let shocks = normals(500, 61);
+    // A random walk whose shocks change scale, where the robust z* differs from z
+    let walk: Vec<f64> = shocks
+        .iter()
+        .enumerate()
+        .scan(0.0, |level, (i, e)| {
+            *level += 0.02 + e * if i % 100 < 50 { 1.0 } else { 2.5 };
+            Some(*level)
+        })
+        .collect();
+    let mut ar = 0.0;
+    let reverting: Vec<f64> = shocks
+        .iter()
+        .map(|e| {
+            ar = 0.7 * ar + e;
+            ar
+        })
+        .collect();
+
+    for (name, data) in [("walk", &walk), ("reverting", &reverting)] {
+        let result = variance_ratio_test(data, &[]).unwrap();
+        assert_eq!(result.horizons(), vec![2, 4, 8, 16]);
+        for (i, q) in [2, 4, 8, 16].into_iter().enumerate() {
+            let (vr, z) = arch_variance_ratio(data, q, false);
+            let (_, robust_z) = arch_variance_ratio(data, q, true);
+            assert!((result.variance_ratios()[i] - vr).abs() < 1e-12 * vr, "{} q {}", name, q);
+            assert!((result.z_statistics()[i] - z).abs() < 1e-9 * z.abs().max(1.0), "{} q {}", name, q);
+            assert!((result.robust_z_statistics()[i] - robust_z).abs() < 1e-9 * robust_z.abs().max(1.0), "{} q {}", name, q);
+        }
+
+        // Chow-Denning: the largest |z*| with the Sidak bound over four horizons
+        let largest = result.robust_z_statistics().iter().map(|z| z.abs()).fold(0.0, f64::max);
+        assert_eq!(result.chow_denning_robust, largest);
+        let single = result.robust_p_values()[result.robust_z_statistics().iter().position(|z| z.abs() == largest).unwrap()];
+        assert!((result.chow_denning_robust_p_value - (1.0 - (1.0 - single).powi(4))).abs() < 1e-12);
+    }
+    assert!(variance_ratio_test(&reverting, &[]).unwrap().is_mean_reverting);
+    assert!(!variance_ratio_test(&walk, &[]).unwrap().is_mean_reverting);
+    assert!(variance_ratio_test(&walk[..32], &[16]).is_err());
+    assert!(variance_ratio_test(&walk, &[1]).is_err());
+}
@@ -0,0 +1,140 @@
+//! Zivot-Andrews against statistics computed exactly on a short series, a transcription of
+//! statsmodels' `zivot_andrews` break search with fixed lags, and the trimming of the
+//! candidate breaks on short samples.
+
+use adf_test::{zivot_andrews_test, BreakType, LagCriterion};
+use nalgebra::{DMatrix, DVector};
+
+mod common;
+
+use common::normals;
+
+/// Minimum t-statistic on y_{t-1} and the bar before the break that attains it, searching
+/// the breaks `lowest..=highest` with `lags` lagged differences.
+fn break_search(y: &[f64], break_type: BreakType, lags: usize, lowest: usize, highest: usize) -> (f64, usize) {
+    let n = y.len();
+    let first = lags + 1;
+    let dy: Vec<f64> = y.windows(2).map(|w| w[1] - w[0]).collect();
+    let mut best = (f64::INFINITY, 0);
+    for bp in lowest..=highest {
+        let rows: Vec<Vec<f64>> = (first..n)
+            .map(|t| {
+                let mut row = vec![1.0, t as f64];
+                if break_type != BreakType::Trend {
+                    row.push(if t > bp { 1.0 } else { 0.0 });
+                }
+                if break_type != BreakType::Intercept {
+                    row.push(if t > bp { (t - bp) as f64 } else { 0.0 });
+                }
+                row.push(y[t - 1]);
+                row.extend((1..=lags).map(|j| dy[t - 1 - j]));
+                row
+            })
+            .collect();
+        let gamma = rows[0].len() - 1 - lags;
+        let x = DMatrix::from_fn(rows.len(), rows[0].len(), |i, k| rows[i][k]);
+        let lhs = DVector::from_fn(rows.len(), |i, _| dy[first + i - 1]);
+        let xtx_inv = (x.transpose() * &x).try_inverse().unwrap();
+        let beta = &xtx_inv * x.transpose() * &lhs;
+        let resid = &lhs - &x * &beta;
+        let s2 = resid.dot(&resid) / (x.nrows() - x.ncols()) as f64;
+        let t_stat = beta[gamma] / (s2 * xtx_inv[(gamma, gamma)]).sqrt();
+        if t_stat < best.0 {
+            best = (t_stat, bp);
+        }
+    }
+    best
+}
+
+/// Stationary AR(1) around a level that jumps by `jump` after bar `break_at`.
+fn shifted_level(n: usize, break_at: usize, jump: f64, seed: u64) -> Vec<f64> {
+    let mut ar = 0.0;
+    normals(n, seed)
+        .iter()
+        .enumerate()
+        .map(|(t, e)| {
+            ar = 0.5 * ar + e;
+            10.0 + 0.02 * t as f64 + if t > break_at { jump } else { 0.0 } + ar
+        })
+        .collect()
+}
+
+fn random_walk(n: usize, seed: u64) -> Vec<f64> {
+    normals(n, seed)
+        .iter()
+        .scan(0.0, |level, e| {
+            *level += e;
+            Some(*level)
+        })
+        .collect()
+}
+
+#[test]
+fn matches_exact_arithmetic_on_a_level_shift() {
+    // A level of about 1 that jumps to about 6 after bar 9. With 20 bars and no lags the
+    // breaks 3..=16 are searched; the statistics below were computed for every break in
+    // exact rational arithmetic, and the level shift is found where it happened
+    let y = [0.0, 1.0, 0.0, 2.0, 1.0, 0.0, 1.0, 2.0, 1.0, 0.0, 5.0, 6.0, 5.0, 7.0, 6.0, 5.0, 6.0, 7.0, 6.0, 5.0];
+    for (break_type, t_squared, expected) in [
+        (BreakType::Intercept, 693959805.0 / 18560701.0, -6.1146270167),
+        (BreakType::Both, 362544525.0 / 11165594.0, -5.6982271734),
+    ] {
+        let result = zivot_andrews_test(&y, break_type, LagCriterion::Fixed, Some(0)).unwrap();
+        assert_eq!(result.break_index, 9, "{}", break_type);
+        assert!((result.test_statistic + f64::sqrt(t_squared)).abs() < 1e-10, "{}: {}", break_type, result.test_statistic);
+        assert!((result.test_statistic - expected).abs() < 1e-9, "{}", break_type);
+        assert!(result.is_stationary, "{}", break_type);
+    }
+    // A kink in the trend alone fits a jump poorly, and the search settles elsewhere
+    let result = zivot_andrews_test(&y, BreakType::Trend, LagCriterion::Fixed, Some(0)).unwrap();
+    assert_eq!(result.break_index, 14);
+    assert!((result.test_statistic + f64::sqrt(2862490910907.0 / 354146420719.0)).abs() < 1e-10);
+    assert!(!result.is_stationary);
+}
+
+#[test]
+fn matches_the_break_search_with_fixed_lags() {
+    let data = shifted_level(200, 119, 6.0, 3);
+    for break_type in [BreakType::Intercept, BreakType::Trend, BreakType::Both] {
+        for lags in [0, 2] {
+            let result = zivot_andrews_test(&data, break_type, LagCriterion::Fixed, Some(lags)).unwrap();
+            // int(0.15 * 200) = 30 bars trimmed at each end
+            let (statistic, break_index) = break_search(&data, break_type, lags as usize, 30, 169);
+            assert_eq!(result.lags, lags);
+            assert_eq!(result.nobs as usize, 200 - 1 - lags as usize);
+            assert_eq!(result.break_index as usize, break_index, "{} lags {}", break_type, lags);
+            assert!((result.test_statistic - statistic).abs() < 1e-9 * statistic.abs(), "{} lags {}", break_type, lags);
+        }
+    }
+
+    let result = zivot_andrews_test(&data, BreakType::Intercept, LagCriterion::Aic, None).unwrap();
+    assert!((117..=121).contains(&result.break_index), "{}", result.break_index);
+    assert!(result.is_stationary);
+    assert_eq!(result.critical_value_table(), BreakType::Intercept.critical_values());
+    assert_eq!(BreakType::Both.critical_values().five_percent, -5.08);
+}
+
+#[test]
+fn breaks_respect_the_trimming_on_short_samples() {
+    for n in (20..60).step_by(4) {
+        let data = random_walk(n, n as u64);
+        for break_type in [BreakType::Intercept, BreakType::Trend, BreakType::Both] {
+            for (autolag, max_lags) in [(LagCriterion::Aic, None), (LagCriterion::Fixed, Some(4)), (LagCriterion::Fixed, None)] {
+                let Ok(result) = zivot_andrews_test(&data, break_type, autolag, max_lags) else {
+                    continue;
+                };
+                let break_index = result.break_index as usize;
+                let trim = (0.15 * n as f64) as usize;
+                let first = n - result.nobs as usize;
+                let row_trim = ((0.15 * result.nobs as f64) as usize).max(1);
+                let case = format!("n {} {} {:?} {:?}", n, break_type, autolag, max_lags);
+                // At least 15% of the series and of the regression rows on either side
+                assert!(break_index + 1 >= trim && n - 1 - break_index >= trim, "{}: {}", case, break_index);
+                assert!(break_index + 1 - first >= row_trim, "{}: {}", case, break_index);
+                assert!(n - 1 - break_index >= row_trim, "{}: {}", case, break_index);
+            }
+        }
+    }
+    let short = random_walk(19, 1);
+    assert!(zivot_andrews_test(&short, BreakType::Both, LagCriterion::Aic, None).is_err());
+}
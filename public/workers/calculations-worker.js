@@ -601,14 +601,6 @@ const adfTestWasmEnhanced = async (data, seriesType, modelType) => {
     message: `🧪 Enhanced ADF Test: Received ${data.length} raw data points for ${seriesType}. Cleaned to ${cleanData.length} points.`,
   })
 
-  if (cleanData.length < 5) {
-    self.postMessage({
-      type: "debug",
-      message: `⚠️ Enhanced ADF Test: Not enough clean data points (${cleanData.length}) for ADF test. Returning default.`,
-    })
-    return { statistic: 0, pValue: 1, criticalValues: { "1%": 0, "5%": 0, "10%": 0 }, isStationary: false }
-  }
-
   try {
     await initializeWasm() // Ensure WASM is loaded
 
@@ -636,12 +628,10 @@ const adfTestWasmEnhanced = async (data, seriesType, modelType) => {
       calculationMethod: "Enhanced WASM with nalgebra"
     }
   } catch (error) {
+    // The WASM test throws with the reason (insufficient data, constant series, ...); report
+    // it instead of a made-up non-stationary result
     console.error("Error running Enhanced ADF test with WASM:", error)
-    self.postMessage({ type: "error", message: `Enhanced ADF Test WASM error: ${error.message}` })
-    
-    // Fallback to old method
-    self.postMessage({ type: "debug", message: "⚠️ Falling back to basic p-value lookup method..." })
-    return { statistic: 0, pValue: 1, criticalValues: { "1%": 0, "5%": 0, "10%": 0 }, isStationary: false }
+    throw new Error(`ADF test failed for ${seriesType}: ${error.message || error}`)
   }
 }
 